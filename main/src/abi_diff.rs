@@ -0,0 +1,288 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Compares the external interfaces of two versions of a contract, such as before upgrading the
+//! implementation behind a proxy.
+
+use crate::{
+    export_abi::{get_json_abi_in, load_abi},
+    macros::*,
+    util::{
+        color::{Color, DebugColor},
+        sys,
+    },
+    AbiDiffArgs,
+};
+use alloy::{
+    json_abi::{Event, Function, JsonAbi, Param, StateMutability},
+    primitives::{Address, Selector, B256},
+};
+use eyre::{bail, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    NonBreaking,
+    Breaking,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Change {
+    pub severity: Severity,
+    pub description: String,
+}
+
+pub fn abi_diff(args: &AbiDiffArgs) -> Result<()> {
+    let old = load_side(&args.old, args.rust_features.clone())?;
+    let new = load_side(&args.new, args.rust_features.clone())?;
+    report(&diff(&old, &new), "interface")
+}
+
+/// Prints the changes between two versions of a contract's `noun`, failing if any are breaking.
+pub fn report(changes: &[Change], noun: &str) -> Result<()> {
+    for change in changes {
+        match change.severity {
+            Severity::Breaking => println!("{} {}", "breaking:".red(), change.description),
+            Severity::NonBreaking => println!("{} {}", "compatible:".mint(), change.description),
+        }
+    }
+    let breaking = changes
+        .iter()
+        .filter(|x| x.severity == Severity::Breaking)
+        .count();
+    if breaking > 0 {
+        bail!("found {breaking} breaking {noun} changes");
+    }
+    match changes.is_empty() {
+        true => greyln!("{noun}s are identical"),
+        false => greyln!("no breaking {noun} changes"),
+    }
+    Ok(())
+}
+
+/// Loads a side of the diff, which is an ABI file, a project directory, or a git revision of the
+/// project in the current directory. Deployed contracts are rejected, as their ABI isn't onchain.
+fn load_side(side: &str, rust_features: Option<Vec<String>>) -> Result<JsonAbi> {
+    let path = Path::new(side);
+    if path.is_dir() {
+        return get_json_abi_in(path, rust_features);
+    }
+    if path.exists() {
+        return load_abi(Some(path), None);
+    }
+    if side.parse::<Address>().is_ok() {
+        bail!("cannot diff deployed contract {side}, as its ABI isn't stored onchain: pass the ABI file or git revision it was built from instead");
+    }
+    export_revision(side, rust_features)
+}
+
+/// Exports the ABI of the project at a git revision, checked out in a temporary worktree.
+fn export_revision(revision: &str, rust_features: Option<Vec<String>>) -> Result<JsonAbi> {
+    greyln!("checking out {}...", revision.debug_lavender());
+    sys::with_git_revision(revision, |dir| get_json_abi_in(dir, rust_features))
+}
+
+fn functions(abi: &JsonAbi) -> BTreeMap<Selector, &Function> {
+    abi.functions().map(|x| (x.selector(), x)).collect()
+}
+
+fn events(abi: &JsonAbi) -> BTreeMap<B256, &Event> {
+    abi.events().map(|x| (x.selector(), x)).collect()
+}
+
+/// Classifies the differences between the interfaces of two versions of a contract.
+pub fn diff(old: &JsonAbi, new: &JsonAbi) -> Vec<Change> {
+    let mut changes = vec![];
+    let mut push = |severity, description| {
+        changes.push(Change {
+            severity,
+            description,
+        })
+    };
+
+    let (old_functions, new_functions) = (functions(old), functions(new));
+    let mut replacements: BTreeSet<Selector> = BTreeSet::new();
+    for (selector, function) in &old_functions {
+        let signature = function.signature();
+        let Some(updated) = new_functions.get(selector) else {
+            // a new overload of the same name is likely the function with different parameters
+            let replacement = new_functions.iter().find(|(selector, x)| {
+                x.name == function.name
+                    && !old_functions.contains_key(*selector)
+                    && !replacements.contains(*selector)
+            });
+            let description = match replacement {
+                Some((selector, x)) => {
+                    replacements.insert(*selector);
+                    format!("function {signature} changed to {}", x.signature())
+                }
+                None => format!("function {signature} removed"),
+            };
+            push(Severity::Breaking, description);
+            continue;
+        };
+
+        let outputs = |x: &[Param]| {
+            let types: Vec<_> = x.iter().map(|x| x.selector_type()).collect();
+            format!("({})", types.join(","))
+        };
+        let (old_outputs, new_outputs) = (outputs(&function.outputs), outputs(&updated.outputs));
+        if old_outputs != new_outputs {
+            push(
+                Severity::Breaking,
+                format!("function {signature} now returns {new_outputs} instead of {old_outputs}"),
+            );
+        }
+
+        let (from, to) = (function.state_mutability, updated.state_mutability);
+        if from != to {
+            let read_only = |x| matches!(x, StateMutability::Pure | StateMutability::View);
+            // callers may send value to payable functions and staticcall read-only ones
+            let severity = match (from, to) {
+                (StateMutability::Payable, _) => Severity::Breaking,
+                (from, to) if read_only(from) && !read_only(to) => Severity::Breaking,
+                _ => Severity::NonBreaking,
+            };
+            push(
+                severity,
+                format!(
+                    "function {signature} changed from {} to {}",
+                    from.as_json_str(),
+                    to.as_json_str()
+                ),
+            );
+        }
+    }
+    for (selector, function) in &new_functions {
+        if !old_functions.contains_key(selector) && !replacements.contains(selector) {
+            push(
+                Severity::NonBreaking,
+                format!("function {} added", function.signature()),
+            );
+        }
+    }
+
+    let (old_events, new_events) = (events(old), events(new));
+    for (topic, event) in &old_events {
+        let signature = event.signature();
+        match new_events.get(topic) {
+            None => push(Severity::Breaking, format!("event {signature} removed")),
+            Some(updated) => {
+                let indexed =
+                    |x: &Event| -> Vec<_> { x.inputs.iter().map(|x| x.indexed).collect() };
+                if indexed(event) != indexed(updated) || event.anonymous != updated.anonymous {
+                    push(
+                        Severity::Breaking,
+                        format!("event {signature} changed which fields are indexed"),
+                    );
+                }
+            }
+        }
+    }
+    for (topic, event) in &new_events {
+        if !old_events.contains_key(topic) {
+            push(
+                Severity::NonBreaking,
+                format!("event {} added", event.signature()),
+            );
+        }
+    }
+
+    let errors = |abi: &JsonAbi| -> BTreeMap<Selector, String> {
+        abi.errors()
+            .map(|x| (x.selector(), x.signature()))
+            .collect()
+    };
+    let (old_errors, new_errors) = (errors(old), errors(new));
+    for (selector, signature) in &old_errors {
+        if !new_errors.contains_key(selector) {
+            push(Severity::NonBreaking, format!("error {signature} removed"));
+        }
+    }
+    for (selector, signature) in &new_errors {
+        if !old_errors.contains_key(selector) {
+            push(Severity::NonBreaking, format!("error {signature} added"));
+        }
+    }
+
+    match (&old.receive, &new.receive) {
+        (Some(_), None) => push(Severity::Breaking, "receive function removed".into()),
+        (None, Some(_)) => push(Severity::NonBreaking, "receive function added".into()),
+        _ => {}
+    }
+    match (&old.fallback, &new.fallback) {
+        (Some(_), None) => push(Severity::Breaking, "fallback function removed".into()),
+        (None, Some(_)) => push(Severity::NonBreaking, "fallback function added".into()),
+        (Some(from), Some(to)) if from.state_mutability != to.state_mutability => {
+            let severity = match from.state_mutability {
+                StateMutability::Payable => Severity::Breaking,
+                _ => Severity::NonBreaking,
+            };
+            push(severity, "fallback function changed payability".into());
+        }
+        _ => {}
+    }
+
+    changes.sort_by_key(|x| std::cmp::Reverse(x.severity));
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_changes() {
+        let old = JsonAbi::parse([
+            "function balanceOf(address owner) external view returns (uint256)",
+            "function deposit() external payable",
+            "function total() external view returns (uint256)",
+            "function burn(uint256 amount) external",
+            "function mint(uint256 amount) external",
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+            "error Unauthorized()",
+        ])
+        .unwrap();
+        let new = JsonAbi::parse([
+            "function balanceOf(address account) external view returns (uint256)",
+            "function deposit() external",
+            "function total() external view returns (uint128)",
+            "function burn(uint256 amount) external view",
+            "function mint(address to, uint256 amount) external",
+            "function pause() external",
+            "event Transfer(address indexed from, address to, uint256 value)",
+            "error Paused()",
+        ])
+        .unwrap();
+
+        let changes = diff(&old, &new);
+        let breaking: Vec<_> = changes
+            .iter()
+            .filter(|x| x.severity == Severity::Breaking)
+            .map(|x| x.description.as_str())
+            .collect();
+        let compatible: Vec<_> = changes
+            .iter()
+            .filter(|x| x.severity == Severity::NonBreaking)
+            .map(|x| x.description.as_str())
+            .collect();
+
+        assert!(breaking.contains(&"function deposit() changed from payable to nonpayable"));
+        assert!(breaking.contains(&"function total() now returns (uint128) instead of (uint256)"));
+        assert!(breaking.contains(&"function mint(uint256) changed to mint(address,uint256)"));
+        assert!(breaking
+            .contains(&"event Transfer(address,address,uint256) changed which fields are indexed"));
+        assert_eq!(breaking.len(), 4);
+
+        assert!(compatible.contains(&"function burn(uint256) changed from nonpayable to view"));
+        assert!(compatible.contains(&"function pause() added"));
+        assert!(compatible.contains(&"error Unauthorized() removed"));
+        assert!(compatible.contains(&"error Paused() added"));
+        assert_eq!(compatible.len(), 4);
+
+        assert!(diff(&old, &old).is_empty());
+    }
+}
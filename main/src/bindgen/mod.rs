@@ -0,0 +1,66 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Generates typed client bindings for a contract from its ABI.
+
+use crate::{
+    export_abi::load_abi,
+    macros::*,
+    project,
+    util::{
+        color::{Color, DebugColor},
+        text::pascal_case,
+    },
+    BindgenArgs, BindgenLang,
+};
+use alloy::primitives::Address;
+use eyre::{eyre, Result, WrapErr};
+use std::{collections::BTreeMap, fs, path::Path};
+
+mod rust;
+mod ts;
+
+pub fn bindgen(args: &BindgenArgs) -> Result<()> {
+    let abi = load_abi(args.abi.as_deref(), args.rust_features.clone())?;
+    let name = match &args.name {
+        Some(name) => name.clone(),
+        None => {
+            let name = project::extract_cargo_lib_or_project_name(&"Cargo.toml".into())?;
+            name.trim_matches('"').to_owned()
+        }
+    };
+    let contract = pascal_case(&name);
+
+    match args.lang {
+        BindgenLang::Rust => rust::generate(&abi, &name, &contract, &args.out_dir)?,
+        BindgenLang::Ts => {
+            let deployments = match &args.deployments {
+                Some(path) => load_deployments(path)?,
+                None => BTreeMap::new(),
+            };
+            ts::generate(&abi, &name, &contract, &deployments, &args.out_dir)?
+        }
+    }
+    greyln!(
+        "wrote bindings for {} to {}",
+        contract.debug_lavender(),
+        args.out_dir.debug_lavender()
+    );
+    Ok(())
+}
+
+/// Loads the addresses a contract is deployed at, from a hand-written JSON object keyed by chain
+/// ID.
+fn load_deployments(path: &Path) -> Result<BTreeMap<u64, Address>> {
+    let data =
+        fs::read_to_string(path).wrap_err_with(|| eyre!("failed to read {}", path.display()))?;
+    serde_json::from_str(&data).wrap_err_with(|| eyre!("invalid deployments {}", path.display()))
+}
+
+/// Writes a generated file, creating its directory if needed.
+fn write(path: &Path, contents: &str) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, contents).wrap_err_with(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,79 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Generates a crate of `alloy::sol!` bindings, which provide call builders, event decoders and
+//! an enum of the contract's errors.
+
+use super::write;
+use alloy::json_abi::{JsonAbi, ToSolConfig};
+use eyre::Result;
+use std::path::Path;
+
+pub fn generate(abi: &JsonAbi, name: &str, contract: &str, out_dir: &Path) -> Result<()> {
+    let crate_name = format!("{name}-bindings").replace('_', "-");
+    let manifest = format!(
+        r#"[package]
+name = "{crate_name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+alloy = {{ version = "1.0", features = ["contract", "sol-types"] }}
+"#
+    );
+    write(&out_dir.join("Cargo.toml"), &manifest)?;
+    write(
+        &out_dir.join("src").join("lib.rs"),
+        &lib(abi, name, contract),
+    )
+}
+
+fn lib(abi: &JsonAbi, name: &str, contract: &str) -> String {
+    let config = ToSolConfig::new()
+        .print_constructors(true)
+        .for_sol_macro(true);
+    let interface = abi.to_sol(contract, Some(config));
+
+    let mut lib = format!(
+        "//! Typed bindings for the `{name}` Stylus contract, generated by `cargo stylus bindgen`.\n\n"
+    );
+    lib += "alloy::sol! {\n    #![sol(rpc, all_derives)]\n\n";
+    for line in interface.lines() {
+        match line.is_empty() {
+            true => lib += "\n",
+            false => lib += &format!("    {line}\n"),
+        }
+    }
+    lib += "}\n";
+    lib
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_crate() {
+        let abi = JsonAbi::parse([
+            "constructor(address owner)",
+            "function transfer(address to, uint256 value) external returns (bool)",
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+            "error Unauthorized(address caller)",
+        ])
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        generate(&abi, "my_token", "MyToken", dir.path()).unwrap();
+
+        let manifest = std::fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+        assert!(manifest.contains(r#"name = "my-token-bindings""#));
+
+        let lib = std::fs::read_to_string(dir.path().join("src/lib.rs")).unwrap();
+        assert!(lib.contains("#![sol(rpc, all_derives)]"));
+        assert!(lib.contains("interface MyToken {"));
+        assert!(lib.contains("constructor(address owner);"));
+        assert!(lib.contains("error Unauthorized(address caller);"));
+        assert!(
+            lib.contains("function transfer(address to, uint256 value) external returns (bool);")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Generates a TypeScript module holding the ABI `as const`, the types of the contract's
+//! functions and events, and the addresses given for its deployments.
+
+use super::write;
+use alloy::{
+    json_abi::{JsonAbi, Param},
+    primitives::Address,
+};
+use eyre::Result;
+use std::{collections::BTreeMap, path::Path};
+
+pub fn generate(
+    abi: &JsonAbi,
+    name: &str,
+    contract: &str,
+    deployments: &BTreeMap<u64, Address>,
+    out_dir: &Path,
+) -> Result<()> {
+    let module = module(abi, name, contract, deployments)?;
+    write(&out_dir.join(format!("{contract}.ts")), &module)
+}
+
+fn module(
+    abi: &JsonAbi,
+    name: &str,
+    contract: &str,
+    deployments: &BTreeMap<u64, Address>,
+) -> Result<String> {
+    let mut chars = contract.chars();
+    let prefix: String = chars
+        .next()
+        .map(|x| x.to_lowercase().chain(chars).collect())
+        .unwrap_or_default();
+
+    let mut out = format!(
+        "// Typed bindings for the `{name}` Stylus contract, generated by `cargo stylus bindgen`.\n\n"
+    );
+    out += "export type Hex = `0x${string}`;\n\n";
+    out += &format!(
+        "export const {prefix}Abi = {} as const;\n\n",
+        serde_json::to_string_pretty(abi)?
+    );
+
+    out += &format!("export type {contract}Functions = {{\n");
+    for function in abi.functions() {
+        // overloads are keyed by signature
+        let key = match abi.function(&function.name).map_or(0, Vec::len) {
+            1 => function.name.clone(),
+            _ => format!("\"{}\"", function.signature()),
+        };
+        out += &format!("  {key}: {{\n");
+        out += &format!("    inputs: {};\n", tuple(&function.inputs));
+        out += &format!("    outputs: {};\n", tuple(&function.outputs));
+        out += &format!(
+            "    stateMutability: \"{}\";\n",
+            function.state_mutability.as_json_str()
+        );
+        out += "  };\n";
+    }
+    out += "};\n\n";
+
+    out += &format!("export type {contract}Events = {{\n");
+    for event in abi.events() {
+        let key = match abi.event(&event.name).map_or(0, Vec::len) {
+            1 => event.name.clone(),
+            _ => format!("\"{}\"", event.signature()),
+        };
+        let fields: Vec<_> = event
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, x)| {
+                let name = match x.name.is_empty() {
+                    true => index.to_string(),
+                    false => x.name.clone(),
+                };
+                format!("{name}: {}", ts_type(&x.ty, &x.components))
+            })
+            .collect();
+        out += &format!("  {key}: {{ {} }};\n", fields.join("; "));
+    }
+    out += "};\n\n";
+
+    out += &format!("export const {prefix}Addresses: {{ readonly [chainId: number]: Hex }} = {{\n");
+    for (chain_id, address) in deployments {
+        out += &format!("  {chain_id}: \"{address}\",\n");
+    }
+    out += "};\n";
+    Ok(out)
+}
+
+/// A tuple type of parameters, labeled when they're all named.
+fn tuple(params: &[Param]) -> String {
+    let labeled = params.iter().all(|x| !x.name.is_empty());
+    let types: Vec<_> = params
+        .iter()
+        .map(|x| {
+            let ty = ts_type(&x.ty, &x.components);
+            match labeled {
+                true => format!("{}: {ty}", x.name),
+                false => ty,
+            }
+        })
+        .collect();
+    format!("readonly [{}]", types.join(", "))
+}
+
+/// The TypeScript type of a Solidity value, with integers too large for a `number` as `bigint`.
+fn ts_type(ty: &str, components: &[Param]) -> String {
+    if let Some(open) = ty.strip_suffix(']').and_then(|x| x.rfind('[')) {
+        return format!("readonly {}[]", ts_type(&ty[..open], components));
+    }
+    if ty == "tuple" {
+        if components.iter().any(|x| x.name.is_empty()) {
+            return tuple(components);
+        }
+        let fields: Vec<_> = components
+            .iter()
+            .map(|x| format!("{}: {}", x.name, ts_type(&x.ty, &x.components)))
+            .collect();
+        return format!("{{ {} }}", fields.join("; "));
+    }
+    let bits = |prefix| ty.strip_prefix(prefix).map(|x| x.parse().unwrap_or(256));
+    match ty {
+        "bool" => "boolean".into(),
+        "string" => "string".into(),
+        "address" => "Hex".into(),
+        _ if ty.starts_with("bytes") => "Hex".into(),
+        _ => match bits("uint").or_else(|| bits("int")) {
+            Some(bits) if bits <= 48 => "number".into(),
+            Some(_) => "bigint".into(),
+            None => "unknown".into(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export_abi::parse_interface;
+    use alloy::primitives::address;
+
+    #[test]
+    fn generate_module() {
+        let abi = parse_interface(
+            "interface IVault {
+                function deposit(Deposit[] memory deposits) external payable returns (uint256);
+                function mint(uint256) external;
+                function mint(address to, uint256 value) external;
+                event Transfer(address indexed from, address indexed to, uint256 value);
+                struct Deposit { address owner; uint64 amount; }
+            }",
+        )
+        .unwrap();
+        let deployments =
+            BTreeMap::from([(421614, address!("00000000000000000000000000000000000000AA"))]);
+        let module = module(&abi, "my_vault", "MyVault", &deployments).unwrap();
+
+        assert!(module.contains("export const myVaultAbi = ["));
+        assert!(module.contains("] as const;"));
+        assert!(module
+            .contains("inputs: readonly [deposits: readonly { owner: Hex; amount: bigint }[]];"));
+        assert!(module.contains("outputs: readonly [bigint];"));
+        assert!(module.contains("stateMutability: \"payable\";"));
+        assert!(module.contains("\"mint(uint256)\": {"));
+        assert!(module.contains("inputs: readonly [bigint];"));
+        assert!(module.contains("Transfer: { from: Hex; to: Hex; value: bigint };"));
+        let address = deployments[&421614];
+        assert!(module.contains(&format!("421614: \"{address}\",")));
+    }
+}
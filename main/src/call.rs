@@ -0,0 +1,192 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Calls the functions of a deployed contract by name, encoding arguments and decoding results
+//! with the contract's ABI.
+
+use crate::{
+    export_abi::load_abi,
+    macros::*,
+    util::{
+        abi::{coerce_args, decode_revert, format_value},
+        color::{Color, DebugColor},
+    },
+    CallArgs, ContractCallOpts, GasFeeConfig, SendArgs,
+};
+use alloy::{
+    dyn_abi::{DynSolValue, FunctionExt, JsonAbiExt},
+    json_abi::{Function, JsonAbi, Param, StateMutability},
+    network::TransactionBuilder,
+    providers::{Provider, ProviderBuilder},
+    rpc::types::TransactionRequest,
+    transports::TransportError,
+};
+use eyre::{bail, eyre, Report, Result, WrapErr};
+
+/// Makes an `eth_call` and prints the decoded return values.
+pub async fn call(args: &CallArgs) -> Result<()> {
+    let target = &args.target;
+    let abi = load_abi(target.abi.as_deref(), target.rust_features.clone())?;
+    let function = resolve_function(&abi, &target.function, target.args.len())?;
+    let mut tx = target.request(function)?;
+    if let Some(from) = args.from {
+        tx = tx.with_from(from);
+    }
+
+    let provider = ProviderBuilder::new().connect(&target.endpoint).await?;
+    let output = provider
+        .call(tx)
+        .block(args.block)
+        .await
+        .map_err(|err| revert_error(&abi, err))?;
+    let values = function
+        .abi_decode_output(&output)
+        .wrap_err_with(|| eyre!("failed to decode return data {output}"))?;
+    print_values(&function.outputs, &values);
+    Ok(())
+}
+
+/// Signs and sends a transaction, waiting for its receipt.
+pub async fn send(args: &SendArgs) -> Result<()> {
+    let target = &args.target;
+    let abi = load_abi(target.abi.as_deref(), target.rust_features.clone())?;
+    let function = resolve_function(&abi, &target.function, target.args.len())?;
+    if function.state_mutability != StateMutability::Payable && !target.value.is_zero() {
+        bail!(
+            "attempting to send Ether to non-payable function {}",
+            function.name
+        );
+    }
+    let mut tx = target.request(function)?;
+    if let Some(max_fee) = args.get_max_fee_per_gas_wei()? {
+        tx = tx.with_max_fee_per_gas(max_fee);
+    }
+
+    let provider = ProviderBuilder::new().connect(&target.endpoint).await?;
+    let chain_id = provider.get_chain_id().await?;
+    let wallet = args.auth.alloy_wallet(chain_id)?;
+    let provider = ProviderBuilder::new()
+        .wallet(wallet)
+        .connect(&target.endpoint)
+        .await?;
+
+    let pending = provider
+        .send_transaction(tx)
+        .await
+        .map_err(|err| revert_error(&abi, err))?;
+    let tx_hash = *pending.tx_hash();
+    greyln!("sent tx: {}", tx_hash.debug_lavender());
+
+    let receipt = pending
+        .get_receipt()
+        .await
+        .wrap_err("tx failed to complete")?;
+    if !receipt.status() {
+        bail!("tx reverted {}", tx_hash.debug_red());
+    }
+    greyln!(
+        "confirmed in block {} using {} gas",
+        receipt.block_number.unwrap_or_default().debug_lavender(),
+        receipt.gas_used.debug_lavender()
+    );
+    Ok(())
+}
+
+impl ContractCallOpts {
+    /// A request calling the function with the given arguments.
+    fn request(&self, function: &Function) -> Result<TransactionRequest> {
+        let values = coerce_args(&function.inputs, &self.args)
+            .wrap_err_with(|| format!("invalid arguments for {}", function.signature()))?;
+        let input = function.abi_encode_input(&values)?;
+        Ok(TransactionRequest::default()
+            .with_to(self.address)
+            .with_value(self.value)
+            .with_input(input))
+    }
+}
+
+/// Finds a function by signature, or by name if it isn't overloaded with the same number of
+/// arguments.
+fn resolve_function<'a>(abi: &'a JsonAbi, function: &str, args: usize) -> Result<&'a Function> {
+    if function.contains('(') {
+        let signature = Function::parse(function)
+            .wrap_err_with(|| format!("invalid function signature: {function}"))?
+            .signature();
+        return abi
+            .functions()
+            .find(|x| x.signature() == signature)
+            .ok_or_else(|| eyre!("ABI has no function {signature}"));
+    }
+
+    let overloads = abi
+        .function(function)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let candidates: Vec<_> = overloads
+        .iter()
+        .filter(|x| x.inputs.len() == args)
+        .collect();
+    match candidates[..] {
+        [function] => Ok(function),
+        [] if overloads.is_empty() => bail!("ABI has no function {function}"),
+        [] => {
+            let want: Vec<_> = overloads
+                .iter()
+                .map(|x| x.inputs.len().to_string())
+                .collect();
+            bail!(
+                "mismatch number of arguments for {function} (want {}; got {args})",
+                want.join(" or ")
+            )
+        }
+        _ => {
+            let signatures: Vec<_> = candidates.iter().map(|x| x.signature()).collect();
+            bail!(
+                "{function} is overloaded, pass one of {} instead",
+                signatures.join(", ")
+            )
+        }
+    }
+}
+
+/// Decodes the revert data of a failed call, if any.
+fn revert_error(abi: &JsonAbi, err: TransportError) -> Report {
+    match err.as_error_resp().and_then(|x| x.as_revert_data()) {
+        Some(data) => eyre!("execution reverted: {}", decode_revert(abi, &data).red()),
+        None => err.into(),
+    }
+}
+
+fn print_values(params: &[Param], values: &[DynSolValue]) {
+    for (param, value) in params.iter().zip(values) {
+        match param.name.is_empty() {
+            true => println!("{}", format_value(value)),
+            false => println!("{}: {}", param.name.lavender(), format_value(value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_functions() {
+        let abi = JsonAbi::parse([
+            "function transfer(address to, uint256 value) external returns (bool)",
+            "function mint(uint256 value) external",
+            "function mint(address to, uint256 value) external",
+        ])
+        .unwrap();
+        let function = resolve_function(&abi, "transfer", 2).unwrap();
+        assert_eq!(function.signature(), "transfer(address,uint256)");
+        let function = resolve_function(&abi, "mint", 1).unwrap();
+        assert_eq!(function.signature(), "mint(uint256)");
+        let function = resolve_function(&abi, "mint(address, uint256)", 2).unwrap();
+        assert_eq!(function.signature(), "mint(address,uint256)");
+
+        assert!(resolve_function(&abi, "transfer", 1).is_err());
+        assert!(resolve_function(&abi, "burn", 1).is_err());
+        assert!(resolve_function(&abi, "burn(uint256)", 1).is_err());
+    }
+}
@@ -164,7 +164,7 @@ impl From<EthCallError> for ErrReport {
 }
 
 /// Checks whether a contract has already been activated with the most recent version of Stylus.
-async fn contract_exists(codehash: B256, provider: &impl Provider) -> Result<bool> {
+pub async fn contract_exists(codehash: B256, provider: &impl Provider) -> Result<bool> {
     let arbwasm = ArbWasm::new(ARB_WASM_ADDRESS, provider);
     match arbwasm.codehashVersion(codehash).call().await {
         Ok(_) => Ok(true),
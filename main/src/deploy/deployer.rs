@@ -5,12 +5,15 @@ use crate::{
     check::ContractCheck,
     deploy::calculate_fee_per_gas,
     macros::*,
-    util::color::{Color, DebugColor, GREY},
+    util::{
+        abi::{coerce_args, json_to_value},
+        color::{Color, DebugColor, GREY},
+    },
     DeployConfig,
 };
 use alloy::{
-    dyn_abi::{DynSolValue, JsonAbiExt, Specifier},
-    json_abi::{Constructor, StateMutability},
+    dyn_abi::{DynSolValue, JsonAbiExt},
+    json_abi::{Constructor, Param, StateMutability},
     network::TransactionBuilder,
     primitives::{address, utils::format_ether, Address, U256},
     providers::{Provider, ProviderBuilder},
@@ -19,6 +22,7 @@ use alloy::{
     sol_types::{SolCall, SolEvent},
 };
 use eyre::{bail, eyre, Context, Result};
+use std::fs;
 
 pub const STYLUS_DEPLOYER_ADDRESS: Address = address!("cEcba2F1DC234f70Dd89F2041029807F8D03A990");
 
@@ -65,28 +69,7 @@ pub async fn parse_constructor_args(
     }
     let tx_value = contract.suggest_fee() + constructor_value;
 
-    let args = &cfg.constructor_args;
-    let params = &constructor.inputs;
-    if args.len() != params.len() {
-        bail!(
-            "mismatch number of constructor arguments (want {}; got {})",
-            params.len(),
-            args.len()
-        );
-    }
-
-    let mut arg_values = Vec::<DynSolValue>::with_capacity(args.len());
-    for (arg, param) in args.iter().zip(params) {
-        let ty = param
-            .resolve()
-            .wrap_err_with(|| format!("could not resolve constructor arg: {param}"))?;
-        let value = ty
-            .coerce_str(arg)
-            .wrap_err_with(|| format!("could not parse constructor arg: {param}"))?;
-        arg_values.push(value);
-    }
-    let calldata_args = constructor.abi_encode_input_raw(&arg_values)?;
-
+    let calldata_args = encode_constructor_args(cfg, constructor)?;
     let mut constructor_calldata = Vec::from(stylus_constructorCall::SELECTOR);
     constructor_calldata.extend(calldata_args);
 
@@ -110,6 +93,62 @@ pub async fn parse_constructor_args(
     })
 }
 
+/// ABI-encodes the constructor arguments, given as pre-encoded calldata, a JSON file keyed by
+/// parameter name, or positional strings.
+fn encode_constructor_args(cfg: &DeployConfig, constructor: &Constructor) -> Result<Vec<u8>> {
+    let params = &constructor.inputs;
+    if let Some(calldata) = &cfg.constructor_calldata {
+        constructor
+            .abi_decode_input(calldata)
+            .wrap_err("constructor calldata doesn't match the constructor")?;
+        return Ok(calldata.to_vec());
+    }
+
+    let arg_values = match &cfg.constructor_args_file {
+        Some(path) => {
+            let data = fs::read_to_string(path)
+                .wrap_err_with(|| format!("failed to read {}", path.display()))?;
+            let json: serde_json::Value = serde_json::from_str(&data)
+                .wrap_err_with(|| format!("invalid constructor args {}", path.display()))?;
+            json_constructor_args(params, &json)?
+        }
+        None => {
+            coerce_args(params, &cfg.constructor_args).wrap_err("invalid constructor arguments")?
+        }
+    };
+    Ok(constructor.abi_encode_input_raw(&arg_values)?)
+}
+
+/// Converts a JSON object of constructor arguments keyed by parameter name, or by position for
+/// unnamed parameters.
+fn json_constructor_args(params: &[Param], json: &serde_json::Value) -> Result<Vec<DynSolValue>> {
+    let Some(fields) = json.as_object() else {
+        bail!("constructor args must be a JSON object keyed by parameter name");
+    };
+    let keys: Vec<_> = params
+        .iter()
+        .enumerate()
+        .map(|(index, x)| match x.name.is_empty() {
+            true => index.to_string(),
+            false => x.name.clone(),
+        })
+        .collect();
+    if let Some(unknown) = fields.keys().find(|x| !keys.contains(x)) {
+        bail!("unknown constructor arg: {unknown}");
+    }
+
+    let mut arg_values = Vec::with_capacity(params.len());
+    for (key, param) in keys.iter().zip(params) {
+        let json = fields
+            .get(key)
+            .ok_or_else(|| eyre!("missing constructor arg: {param}"))?;
+        let value = json_to_value(&param.ty, &param.components, json)
+            .wrap_err_with(|| format!("could not parse constructor arg: {param}"))?;
+        arg_values.push(value);
+    }
+    Ok(arg_values)
+}
+
 /// Deploys, activates, and initializes the contract using the Stylus deployer.
 pub async fn deploy(
     cfg: &DeployConfig,
@@ -65,7 +65,7 @@ pub async fn deploy(cfg: DeployConfig) -> Result<()> {
     };
 
     // Check constructor flags for contracts without constructor
-    if deployer_args.is_none() && !cfg.constructor_args.is_empty() {
+    if deployer_args.is_none() && cfg.has_constructor_args() {
         bail!("constructor arguments set but constructor was not found");
     }
 
@@ -321,6 +321,52 @@ pub fn contract_deployment_calldata(code: &[u8]) -> Vec<u8> {
     deploy
 }
 
+/// Prepares EVM bytecode that activates `program` and then forwards its calldata and callvalue
+/// to it, so that undeployed code injected with a state override can be called in one simulation.
+/// The activation fee is paid from the activator's own balance.
+pub fn activate_and_call_code(program: Address, activate: bool) -> Vec<u8> {
+    let mut code: Vec<u8> = vec![];
+    let mut to_revert = None;
+    if activate {
+        code.push(0x63); // PUSH4
+        code.extend(ArbWasm::activateProgramCall::SELECTOR);
+        code.extend([0x60, 0xe0, 0x1b]); // PUSH1 224 SHL
+        code.extend([0x60, 0x00, 0x52]); // PUSH1 0 MSTORE
+        code.push(0x73); // PUSH20
+        code.extend(program);
+        code.extend([0x60, 0x04, 0x52]); // PUSH1 4 MSTORE
+        code.extend([0x60, 0x00, 0x60, 0x00, 0x60, 0x24, 0x60, 0x00]); // out, out_len, in_len, in
+        code.push(0x47); // SELFBALANCE
+        code.push(0x73); // PUSH20
+        code.extend(ARB_WASM_ADDRESS);
+        code.extend([0x5a, 0xf1]); // GAS CALL
+        code.push(0x15); // ISZERO
+        to_revert = Some(code.len() + 1);
+        code.extend([0x60, 0x00, 0x57]); // PUSH1 revert JUMPI
+    }
+    code.extend([0x36, 0x60, 0x00, 0x60, 0x00, 0x37]); // CALLDATACOPY
+    code.extend([0x60, 0x00, 0x60, 0x00, 0x36, 0x60, 0x00]); // out, out_len, in_len, in
+    code.push(0x34); // CALLVALUE
+    code.push(0x73); // PUSH20
+    code.extend(program);
+    code.extend([0x5a, 0xf1]); // GAS CALL
+    let to_success = code.len() + 1;
+    code.extend([0x60, 0x00, 0x57]); // PUSH1 success JUMPI
+
+    let revert = code.len();
+    code.extend([0x5b, 0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e]); // JUMPDEST RETURNDATACOPY
+    code.extend([0x3d, 0x60, 0x00, 0xfd]); // REVERT
+    let success = code.len();
+    code.extend([0x5b, 0x3d, 0x60, 0x00, 0x60, 0x00, 0x3e]); // JUMPDEST RETURNDATACOPY
+    code.extend([0x3d, 0x60, 0x00, 0xf3]); // RETURN
+
+    code[to_success] = success as u8;
+    if let Some(to_revert) = to_revert {
+        code[to_revert] = revert as u8;
+    }
+    code
+}
+
 pub fn extract_contract_evm_deployment_prelude(calldata: &[u8]) -> Vec<u8> {
     // The length of the prelude, version part is 42 + 1 as per the code
     let metadata_length = 42 + 1;
@@ -353,3 +399,30 @@ pub fn calculate_fee_per_gas<T: GasFeeConfig>(config: &T, gas_price: u128) -> Re
     };
     Ok(fee_per_gas)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn activator_jumps_to_jumpdests() {
+        let program = Address::repeat_byte(0x57);
+        for activate in [true, false] {
+            let code = activate_and_call_code(program, activate);
+            let mut pc = 0;
+            let mut jumps = 0;
+            while pc < code.len() {
+                let op = code[pc];
+                if op == 0x60 && code.get(pc + 2) == Some(&0x57) {
+                    assert_eq!(code[code[pc + 1] as usize], 0x5b);
+                    jumps += 1;
+                }
+                pc += match op {
+                    0x60..=0x7f => 2 + (op - 0x60) as usize,
+                    _ => 1,
+                };
+            }
+            assert_eq!(jumps, if activate { 2 } else { 1 });
+        }
+    }
+}
@@ -2,51 +2,178 @@
 // For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
 
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
 use crate::util::color::Color;
-use eyre::{bail, eyre, Result};
+use clap::ValueEnum;
+use eyre::{bail, eyre, Result, WrapErr};
+use tempfile::NamedTempFile;
 
 use crate::constants::TOOLCHAIN_FILE_NAME;
 use crate::macros::greyln;
 use crate::project::extract_toolchain_channel;
 
+/// Where files are mounted in the container.
+const MOUNT_DIR: &str = "/mnt/stylus";
+
+/// Where the cargo home is mounted in the container, since the user it runs as may not be able to
+/// write to the image's. The paths of crate sources are embedded in the build, so this is the same
+/// whichever user the container runs as.
+const CARGO_HOME: &str = "/cargo-home";
+
+/// Files mounted read-only into the container, which lets the reproducible run read files outside
+/// the project and receive secrets without them appearing in its command line.
+#[derive(Default)]
+pub struct Mounts {
+    files: Vec<(PathBuf, PathBuf)>,
+    secrets: Vec<NamedTempFile>,
+}
+
+impl Mounts {
+    /// Mounts a host file, returning its path in the container.
+    pub fn file(&mut self, path: &Path) -> Result<PathBuf> {
+        let host = path
+            .canonicalize()
+            .wrap_err_with(|| eyre!("failed to read {}", path.display()))?;
+        let name = host
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        Ok(self.mount(host, &name))
+    }
+
+    /// Writes a secret to a temporary file only the user can read, and mounts it.
+    pub fn secret(&mut self, name: &str, contents: &str) -> Result<PathBuf> {
+        let mut file = NamedTempFile::new()?;
+        file.write_all(contents.as_bytes())?;
+        let target = self.mount(file.path().to_owned(), name);
+        self.secrets.push(file);
+        Ok(target)
+    }
+
+    fn mount(&mut self, host: PathBuf, name: &str) -> PathBuf {
+        let target = Path::new(MOUNT_DIR).join(format!("{}-{name}", self.files.len()));
+        self.files.push((host, target.clone()));
+        target
+    }
+}
+
+/// The program that runs the reproducible container.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    /// Picks Docker if it's installed, and Podman otherwise.
+    pub fn detect() -> Self {
+        let installed = |runtime: Self| {
+            runtime
+                .command()
+                .arg("--version")
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|x| x.success())
+        };
+        match installed(Self::Docker) || !installed(Self::Podman) {
+            true => Self::Docker,
+            false => Self::Podman,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Docker => "Docker",
+            Self::Podman => "Podman",
+        }
+    }
+
+    fn command(self) -> Command {
+        match self {
+            Self::Docker => Command::new("docker"),
+            Self::Podman => Command::new("podman"),
+        }
+    }
+
+    /// The arguments that run the container as the owner of the project, so that the files it
+    /// writes aren't owned by root.
+    fn user_args(self, dir: &Path) -> Result<Vec<String>> {
+        let Some((uid, gid)) = owner(dir)? else {
+            return Ok(vec![]);
+        };
+        if uid == 0 {
+            return Ok(vec![]);
+        }
+        Ok(match self {
+            Self::Docker => vec!["--user".into(), format!("{uid}:{gid}")],
+            // rootless Podman maps the user to root in the container unless told to keep their ID
+            Self::Podman => vec!["--userns=keep-id".into()],
+        })
+    }
+}
+
+#[cfg(unix)]
+fn owner(dir: &Path) -> Result<Option<(u32, u32)>> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = dir.metadata()?;
+    Ok(Some((metadata.uid(), metadata.gid())))
+}
+
+#[cfg(not(unix))]
+fn owner(_dir: &Path) -> Result<Option<(u32, u32)>> {
+    Ok(None)
+}
+
 fn image_name(cargo_stylus_version: &str, toolchain_version: &str) -> String {
     format!("cargo-stylus-base-{cargo_stylus_version}-toolchain-{toolchain_version}")
 }
 
-fn image_exists(image_name: &str) -> Result<bool> {
-    let output = Command::new("docker")
-        .arg("images")
+fn image_exists(runtime: ContainerRuntime, image_name: &str) -> Result<bool> {
+    let name = runtime.name();
+    let output = runtime
+        .command()
+        .arg("image")
+        .arg("inspect")
         .arg(image_name)
         .output()
-        .map_err(|e| eyre!("failed to execute Docker command: {e}"))?;
-
-    if !output.status.success() {
-        let stderr = std::str::from_utf8(&output.stderr)
-            .map_err(|e| eyre!("failed to read Docker command stderr: {e}"))?;
-        if stderr.contains("Cannot connect to the Docker daemon") {
-            println!(
-                r#"Cargo stylus deploy|check|verify run in a Docker container by default to ensure deployments
-are reproducible, but Docker is not found in your system. Please install Docker if you wish to create 
-a reproducible deployment, or opt out by using the --no-verify flag for local builds"#
-            );
-            bail!("Docker not running");
-        }
-        bail!(stderr.to_string())
+        .map_err(|e| eyre!("failed to execute {name} command: {e}"))?;
+    if output.status.success() {
+        return Ok(true);
     }
 
-    Ok(output.stdout.iter().filter(|c| **c == b'\n').count() > 1)
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("Cannot connect to the Docker daemon") {
+        println!(
+            r#"Cargo stylus deploy|check|verify run in a container by default to ensure deployments
+are reproducible, but Docker is not running in your system. Please install Docker or Podman if you wish
+to create a reproducible deployment, or opt out by using the --no-verify flag for local builds"#
+        );
+        bail!("{name} not running");
+    }
+    let stderr_lower = stderr.to_lowercase();
+    if stderr_lower.contains("no such image") || stderr_lower.contains("image not known") {
+        return Ok(false);
+    }
+    bail!(stderr.to_string())
 }
 
-fn create_image(cargo_stylus_version: &str, toolchain_version: &str) -> Result<()> {
+fn create_image(
+    runtime: ContainerRuntime,
+    cargo_stylus_version: &str,
+    toolchain_version: &str,
+) -> Result<()> {
     let image_name = image_name(cargo_stylus_version, toolchain_version);
-    if image_exists(&image_name)? {
+    if image_exists(runtime, &image_name)? {
         return Ok(());
     }
-    println!("Building Docker image for Rust toolchain {toolchain_version}");
-    let mut child = Command::new("docker")
+    let name = runtime.name();
+    println!("Building {name} image for Rust toolchain {toolchain_version}");
+    let mut child = runtime
+        .command()
         .arg("build")
         .arg("-t")
         .arg(image_name)
@@ -54,7 +181,7 @@ fn create_image(cargo_stylus_version: &str, toolchain_version: &str) -> Result<(
         .arg("-f-")
         .stdin(Stdio::piped())
         .spawn()
-        .map_err(|e| eyre!("failed to execute Docker command: {e}"))?;
+        .map_err(|e| eyre!("failed to execute {name} command: {e}"))?;
     write!(
         child.stdin.as_mut().unwrap(),
         "\
@@ -70,41 +197,73 @@ fn create_image(cargo_stylus_version: &str, toolchain_version: &str) -> Result<(
     Ok(())
 }
 
-fn run_in_docker_container(
+/// Runs a command in the container, with the project mounted read-only and its `target`
+/// directory mounted writable, along with a cargo home kept in the target directory. Note that
+/// this requires an up-to-date `Cargo.lock`.
+fn run_in_container(
+    runtime: ContainerRuntime,
     cargo_stylus_version: &str,
     toolchain_version: &str,
+    network: &str,
     command_line: &[&str],
+    mounts: &Mounts,
 ) -> Result<()> {
     let image_name = image_name(cargo_stylus_version, toolchain_version);
     let dir =
         std::env::current_dir().map_err(|e| eyre!("failed to find current directory: {e}"))?;
-    Command::new("docker")
+    let target = dir.join("target");
+    let cargo_home = target.join("cargo-home");
+    std::fs::create_dir_all(&cargo_home)
+        .map_err(|e| eyre!("failed to create {}: {e}", cargo_home.display()))?;
+
+    let mut command = runtime.command();
+    command
         .arg("run")
+        .arg("--rm")
         .arg("--network")
-        .arg("host")
+        .arg(network)
         .arg("-w")
         .arg("/source")
         .arg("-v")
-        .arg(format!("{}:/source", dir.as_os_str().to_str().unwrap()))
+        .arg(format!("{}:/source:ro", dir.display()))
+        .arg("-v")
+        .arg(format!("{}:/source/target", target.display()))
+        .arg("-v")
+        .arg(format!("{}:{CARGO_HOME}", cargo_home.display()))
+        .arg("-e")
+        .arg(format!("CARGO_HOME={CARGO_HOME}"))
+        .args(runtime.user_args(&dir)?);
+    for (host, target) in &mounts.files {
+        command
+            .arg("-v")
+            .arg(format!("{}:{}:ro", host.display(), target.display()));
+    }
+    let name = runtime.name();
+    command
         .arg(image_name)
         .args(command_line)
         .spawn()
-        .map_err(|e| eyre!("failed to execute Docker command: {e}"))?
+        .map_err(|e| eyre!("failed to execute {name} command: {e}"))?
         .wait()
         .map_err(|e| eyre!("wait failed: {e}"))?;
     Ok(())
 }
 
 pub fn run_reproducible(
+    runtime: Option<ContainerRuntime>,
     cargo_stylus_version: Option<String>,
+    network: &str,
     command_line: &[String],
+    mounts: &Mounts,
 ) -> Result<()> {
     verify_valid_host()?;
+    let runtime = runtime.unwrap_or_else(ContainerRuntime::detect);
     let toolchain_file_path = PathBuf::from(".").as_path().join(TOOLCHAIN_FILE_NAME);
     let toolchain_channel = extract_toolchain_channel(&toolchain_file_path)?;
     greyln!(
-        "Running reproducible Stylus command with toolchain {}",
-        toolchain_channel.mint()
+        "Running reproducible Stylus command with toolchain {} in {}",
+        toolchain_channel.mint(),
+        runtime.name().mint()
     );
     let cargo_stylus_version =
         cargo_stylus_version.unwrap_or_else(|| env!("CARGO_PKG_VERSION").to_string());
@@ -112,8 +271,15 @@ pub fn run_reproducible(
     for s in command_line.iter() {
         command.push(s);
     }
-    create_image(&cargo_stylus_version, &toolchain_channel)?;
-    run_in_docker_container(&cargo_stylus_version, &toolchain_channel, &command)
+    create_image(runtime, &cargo_stylus_version, &toolchain_channel)?;
+    run_in_container(
+        runtime,
+        &cargo_stylus_version,
+        &toolchain_channel,
+        network,
+        &command,
+        mounts,
+    )
 }
 
 fn verify_valid_host() -> Result<()> {
@@ -161,8 +327,9 @@ mod tests {
             .wait()
             .expect("failed to run docker image rm");
 
-        assert!(!image_exists(&image_name).unwrap());
-        create_image(&cargo_stylus_version, toolchain_version).unwrap();
-        assert!(image_exists(&image_name).unwrap());
+        let runtime = ContainerRuntime::Docker;
+        assert!(!image_exists(runtime, &image_name).unwrap());
+        create_image(runtime, cargo_stylus_version, toolchain_version).unwrap();
+        assert!(image_exists(runtime, &image_name).unwrap());
     }
 }
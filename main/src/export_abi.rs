@@ -3,11 +3,17 @@
 
 use crate::macros::*;
 use crate::util::{color::Color, sys};
-use alloy::json_abi::Constructor;
-use eyre::{bail, Result, WrapErr};
+use alloy::json_abi::{
+    Constructor, Error, Event, Fallback, Function, InternalType, JsonAbi, Param, Receive,
+    StateMutability,
+};
+use eyre::{bail, eyre, Result, WrapErr};
+use serde_json::Value;
 use std::{
+    collections::HashMap,
+    fs,
     io::Write,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{self, Command, Stdio},
 };
 
@@ -17,28 +23,14 @@ pub fn export_abi(
     json: bool,
     rust_features: Option<Vec<String>>,
 ) -> Result<()> {
-    if json && !sys::command_exists("solc") {
-        let link = "https://docs.soliditylang.org/en/latest/installing-solidity.html".red();
-        bail!("solc not found. Please see\n{link}");
-    }
-
     let features = rust_features.map(|feature_list| feature_list.join(","));
-    let mut output = run_export("abi", features)?;
+    let mut output = run_export("abi", features.clone())?;
 
-    // convert the ABI to a JSON file via solc
+    // convert the Solidity interface to a JSON ABI
     if json {
-        let solc = Command::new("solc")
-            .stdin(Stdio::piped())
-            .stderr(Stdio::inherit())
-            .stdout(Stdio::piped())
-            .arg("--abi")
-            .arg("-")
-            .spawn()
-            .wrap_err("failed to run solc")?;
-
-        let mut stdin = solc.stdin.as_ref().unwrap();
-        stdin.write_all(&output)?;
-        output = solc.wait_with_output()?.stdout;
+        let abi = json_abi(Path::new("."), &output, features)?;
+        output = serde_json::to_vec_pretty(&abi)?;
+        output.push(b'\n');
     }
 
     let mut out = sys::file_or_stdout(file)?;
@@ -67,11 +59,69 @@ pub fn get_constructor_signature() -> Result<Option<Constructor>> {
     parse_constructor(&output)
 }
 
+/// Gets the ABI of the Stylus contract by running the export binary and parsing its Solidity
+/// interface.
+pub fn get_json_abi(rust_features: Option<Vec<String>>) -> Result<JsonAbi> {
+    get_json_abi_in(Path::new("."), rust_features)
+}
+
+/// Gets the ABI of the Stylus contract in the given project directory.
+pub fn get_json_abi_in(dir: &Path, rust_features: Option<Vec<String>>) -> Result<JsonAbi> {
+    greyln!("exporting the contract's ABI...");
+    let features = rust_features.map(|feature_list| feature_list.join(","));
+    let output = run_export_in(dir, "abi", features.clone())?;
+    json_abi(dir, &output, features)
+}
+
+/// Parses the exported Solidity interface, adding the constructor which it doesn't include.
+fn json_abi(dir: &Path, interface: &[u8], features: Option<String>) -> Result<JsonAbi> {
+    let mut abi = parse_interface(std::str::from_utf8(interface)?)?;
+    if abi.constructor.is_none() {
+        let output = run_export_in(dir, "constructor", features)?;
+        abi.constructor = parse_constructor(&String::from_utf8(output)?)?;
+    }
+    Ok(abi)
+}
+
+/// Gets the Solidity interfaces exported by the Stylus contract, one for the contract and one for
+/// each of its parents.
+pub fn get_interfaces(rust_features: Option<Vec<String>>) -> Result<Vec<Interface>> {
+    greyln!("exporting the contract's ABI...");
+    let features = rust_features.map(|feature_list| feature_list.join(","));
+    let output = run_export("abi", features)?;
+    parse_interfaces(std::str::from_utf8(&output)?)
+}
+
+/// Loads an ABI from a file holding a JSON ABI, a build artifact with an `abi` field, or a
+/// Solidity interface. Without a file, exports the ABI of the project in the current directory.
+pub fn load_abi(path: Option<&Path>, rust_features: Option<Vec<String>>) -> Result<JsonAbi> {
+    let Some(path) = path else {
+        return get_json_abi(rust_features);
+    };
+    let source =
+        fs::read_to_string(path).wrap_err_with(|| eyre!("failed to read {}", path.display()))?;
+    let context = || eyre!("failed to parse {}", path.display());
+    match source.trim_start().chars().next() {
+        Some('[') => serde_json::from_str(&source).wrap_err_with(context),
+        Some('{') => {
+            let mut json: Value = serde_json::from_str(&source).wrap_err_with(context)?;
+            let abi = json.get_mut("abi").map(Value::take).unwrap_or(json);
+            serde_json::from_value(abi).wrap_err_with(context)
+        }
+        _ => parse_interface(&source).wrap_err_with(context),
+    }
+}
+
 fn run_export(command: &str, features: Option<String>) -> Result<Vec<u8>> {
+    run_export_in(Path::new("."), command, features)
+}
+
+fn run_export_in(dir: &Path, command: &str, features: Option<String>) -> Result<Vec<u8>> {
     let target = format!("--target={}", sys::host_arch()?);
     let features = format!("--features=export-abi,{}", features.unwrap_or_default());
 
     let output = Command::new("cargo")
+        .current_dir(dir)
         .stderr(Stdio::inherit())
         .arg("run")
         .arg("--quiet")
@@ -104,6 +154,357 @@ fn parse_constructor(signature: &str) -> Result<Option<Constructor>> {
     }
 }
 
+/// A struct declared in an exported interface.
+struct StructDef {
+    /// The interface declaring the struct.
+    interface: Option<String>,
+    /// The type and name of each field.
+    fields: Vec<(String, String)>,
+}
+
+/// An interface declared in Solidity.
+#[derive(Debug, Default)]
+pub struct Interface {
+    /// The name of the interface, or empty for declarations outside of any.
+    pub name: String,
+    /// The interfaces it inherits from.
+    pub parents: Vec<String>,
+    pub abi: JsonAbi,
+}
+
+/// Parses the Solidity interfaces printed by the export binary into a single ABI.
+pub fn parse_interface(source: &str) -> Result<JsonAbi> {
+    let interfaces = parse_interfaces(source)?;
+    Ok(interfaces
+        .into_iter()
+        .flat_map(|x| x.abi.into_items())
+        .collect())
+}
+
+/// Parses the Solidity interfaces printed by the export binary, inlining structs as tuples.
+pub fn parse_interfaces(source: &str) -> Result<Vec<Interface>> {
+    let source = strip_comments(source);
+    let (source, structs) = extract_structs(&source)?;
+
+    let mut interfaces = vec![Interface::default()];
+    for statement in source.split(';') {
+        // split off any `interface I is J {` or `}` preceding the statement
+        let (head, declared) = match statement.rfind(['{', '}']) {
+            Some(index) => (&statement[..index], statement[index + 1..].trim()),
+            None => ("", statement.trim()),
+        };
+        let mut rest = head;
+        while let Some(start) = find_word(rest, "interface") {
+            let tail = &rest[start + "interface".len()..];
+            let end = tail.find('{').unwrap_or(tail.len());
+            let (name, parents) = match find_word(&tail[..end], "is") {
+                Some(index) => (&tail[..index], &tail[index + "is".len()..end]),
+                None => (&tail[..end], ""),
+            };
+            interfaces.push(Interface {
+                name: name.trim().to_owned(),
+                parents: parents
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|x| !x.is_empty())
+                    .map(str::to_owned)
+                    .collect(),
+                abi: JsonAbi::default(),
+            });
+            rest = &tail[end..];
+        }
+
+        let Some(keyword) = declared.split(['(', ' ']).next() else {
+            continue;
+        };
+        let Some(Interface { abi, .. }) = interfaces.last_mut() else {
+            continue;
+        };
+        let statement = expand_structs(declared, &structs);
+        let context = || eyre!("failed to parse {declared}");
+        let groups = paren_groups(declared);
+        let declared_types = |index: usize| groups.get(index).map(|x| param_types(x));
+
+        match keyword {
+            "function" => {
+                let mut function = Function::parse(&statement).wrap_err_with(context)?;
+                for (param, ty) in function
+                    .inputs
+                    .iter_mut()
+                    .zip(declared_types(0).unwrap_or_default())
+                {
+                    name_struct(
+                        ty,
+                        &mut param.components,
+                        &mut param.internal_type,
+                        &structs,
+                    );
+                }
+                for (param, ty) in function
+                    .outputs
+                    .iter_mut()
+                    .zip(declared_types(1).unwrap_or_default())
+                {
+                    name_struct(
+                        ty,
+                        &mut param.components,
+                        &mut param.internal_type,
+                        &structs,
+                    );
+                }
+                abi.functions
+                    .entry(function.name.clone())
+                    .or_default()
+                    .push(function);
+            }
+            "event" => {
+                let mut event = Event::parse(&statement).wrap_err_with(context)?;
+                for (param, ty) in event
+                    .inputs
+                    .iter_mut()
+                    .zip(declared_types(0).unwrap_or_default())
+                {
+                    name_struct(
+                        ty,
+                        &mut param.components,
+                        &mut param.internal_type,
+                        &structs,
+                    );
+                }
+                abi.events
+                    .entry(event.name.clone())
+                    .or_default()
+                    .push(event);
+            }
+            "error" => {
+                let mut error = Error::parse(&statement).wrap_err_with(context)?;
+                for (param, ty) in error
+                    .inputs
+                    .iter_mut()
+                    .zip(declared_types(0).unwrap_or_default())
+                {
+                    name_struct(
+                        ty,
+                        &mut param.components,
+                        &mut param.internal_type,
+                        &structs,
+                    );
+                }
+                abi.errors
+                    .entry(error.name.clone())
+                    .or_default()
+                    .push(error);
+            }
+            "constructor" => {
+                let mut constructor = Constructor::parse(&statement).wrap_err_with(context)?;
+                for (param, ty) in constructor
+                    .inputs
+                    .iter_mut()
+                    .zip(declared_types(0).unwrap_or_default())
+                {
+                    name_struct(
+                        ty,
+                        &mut param.components,
+                        &mut param.internal_type,
+                        &structs,
+                    );
+                }
+                abi.constructor = Some(constructor);
+            }
+            "receive" => {
+                abi.receive = Some(Receive {
+                    state_mutability: StateMutability::Payable,
+                })
+            }
+            "fallback" => {
+                let state_mutability = match find_word(declared, "payable") {
+                    Some(_) => StateMutability::Payable,
+                    None => StateMutability::NonPayable,
+                };
+                abi.fallback = Some(Fallback { state_mutability });
+            }
+            _ => {}
+        }
+    }
+    interfaces.retain(|x| !x.name.is_empty() || !x.abi.is_empty());
+    Ok(interfaces)
+}
+
+fn strip_comments(source: &str) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut rest = source;
+    while !rest.is_empty() {
+        if let Some(tail) = rest.strip_prefix("//") {
+            rest = tail.find('\n').map(|x| &tail[x..]).unwrap_or_default();
+        } else if let Some(tail) = rest.strip_prefix("/*") {
+            rest = tail.find("*/").map(|x| &tail[x + 2..]).unwrap_or_default();
+        } else {
+            let next = rest.chars().next().unwrap();
+            output.push(next);
+            rest = &rest[next.len_utf8()..];
+        }
+    }
+    output
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Finds `word` in `source` where it isn't part of a longer identifier.
+fn find_word(source: &str, word: &str) -> Option<usize> {
+    source.match_indices(word).map(|(x, _)| x).find(|&x| {
+        let before = source[..x].chars().next_back();
+        let after = source[x + word.len()..].chars().next();
+        !before.is_some_and(is_ident) && !after.is_some_and(is_ident)
+    })
+}
+
+/// Removes struct definitions from the source, returning them by name.
+fn extract_structs(source: &str) -> Result<(String, HashMap<String, StructDef>)> {
+    let mut structs = HashMap::new();
+    let mut output = String::with_capacity(source.len());
+    let mut interface = None;
+    let mut rest = source;
+    while let Some(start) = find_word(rest, "struct") {
+        let before = &rest[..start];
+        output.push_str(before);
+        // structs are declared inside the most recent interface, if any
+        let mut words = before.split_whitespace().peekable();
+        while let Some(word) = words.next() {
+            if word == "interface" {
+                interface = words.peek().map(|x| x.trim_end_matches('{').to_owned());
+            }
+        }
+
+        let tail = &rest[start + "struct".len()..];
+        let (Some(open), Some(close)) = (tail.find('{'), tail.find('}')) else {
+            bail!("unterminated struct in {}", tail.trim());
+        };
+        let name = tail[..open].trim().to_owned();
+        let mut fields = vec![];
+        for field in tail[open + 1..close].split(';').map(str::trim) {
+            if let Some((ty, name)) = field.rsplit_once(char::is_whitespace) {
+                fields.push((ty.trim().to_owned(), name.to_owned()));
+            }
+        }
+        let def = StructDef {
+            interface: interface.clone(),
+            fields,
+        };
+        structs.insert(name, def);
+        rest = &tail[close + 1..];
+    }
+    output.push_str(rest);
+    Ok((output, structs))
+}
+
+/// Replaces struct names, possibly qualified by their interface, with their tuple types.
+fn expand_structs(statement: &str, structs: &HashMap<String, StructDef>) -> String {
+    let is_path = |c: char| is_ident(c) || c == '.';
+    let mut output = String::with_capacity(statement.len());
+    let mut rest = statement;
+    while let Some(start) = rest.find(is_path) {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(|c| !is_path(c)).unwrap_or(rest.len());
+        let path = &rest[..end];
+        let name = path.rsplit('.').next().unwrap_or(path);
+        match structs.get(name) {
+            Some(def) => {
+                let types: Vec<_> = def
+                    .fields
+                    .iter()
+                    .map(|(ty, _)| expand_structs(ty, structs))
+                    .collect();
+                output.push_str(&format!("({})", types.join(",")));
+            }
+            None => output.push_str(path),
+        }
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// The contents of the top-level parentheses of a statement, such as a function's parameters and
+/// its return values.
+fn paren_groups(statement: &str) -> Vec<&str> {
+    let mut groups = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in statement.char_indices() {
+        match c {
+            '(' => {
+                if depth == 0 {
+                    start = index + 1;
+                }
+                depth += 1;
+            }
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    groups.push(&statement[start..index]);
+                }
+            }
+            _ => {}
+        }
+    }
+    groups
+}
+
+/// The declared type of each parameter in a list like `Deposit memory deposit, uint256 amount`.
+fn param_types(list: &str) -> Vec<&str> {
+    let mut types = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in list.char_indices().chain([(list.len(), ',')]) {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                let param = list[start..index].trim();
+                if !param.is_empty() {
+                    types.push(param.split_whitespace().next().unwrap_or_default());
+                }
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    types
+}
+
+/// Restores the field names and struct name lost when a struct parameter was inlined as a tuple.
+fn name_struct(
+    declared: &str,
+    components: &mut [Param],
+    internal_type: &mut Option<InternalType>,
+    structs: &HashMap<String, StructDef>,
+) {
+    let base = declared.split('[').next().unwrap_or(declared);
+    let (qualifier, name) = match base.rsplit_once('.') {
+        Some((qualifier, name)) => (Some(qualifier.to_owned()), name),
+        None => (None, base),
+    };
+    let Some(def) = structs.get(name) else {
+        return;
+    };
+    *internal_type = Some(InternalType::Struct {
+        contract: qualifier.or_else(|| def.interface.clone()),
+        ty: format!("{name}{}", &declared[base.len()..]),
+    });
+    for (component, (ty, field)) in components.iter_mut().zip(&def.fields) {
+        component.name = field.clone();
+        name_struct(
+            ty,
+            &mut component.components,
+            &mut component.internal_type,
+            structs,
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -187,4 +588,73 @@ interface ICounter  {
             assert_eq!(constructor, expected);
         }
     }
+
+    #[test]
+    fn parse_interfaces() {
+        let source = "/**
+ * This file was automatically generated by Stylus and represents a Rust program.
+ */
+
+// SPDX-License-Identifier: MIT-OR-APACHE-2.0
+pragma solidity ^0.8.23;
+
+interface IErc20 {
+    function balanceOf(address owner) external view returns (uint256);
+
+    event Transfer(address indexed from, address indexed to, uint256 value);
+}
+
+interface IVault is IErc20 {
+    function deposit(IVault.Deposit memory deposit) external payable returns (uint256);
+
+    function deposits(address owner) external view returns (Deposit[] memory);
+
+    error InsufficientBalance(address, uint256);
+
+    struct Deposit {
+        address owner;
+        uint256 amount;
+    }
+}";
+        let abi = parse_interface(source).expect("failed to parse");
+        let deposit = &abi.functions["deposit"][0];
+        assert_eq!(deposit.signature(), "deposit((address,uint256))");
+        assert_eq!(deposit.state_mutability, StateMutability::Payable);
+        assert_eq!(deposit.inputs[0].name, "deposit");
+        assert_eq!(deposit.inputs[0].components[1].name, "amount");
+        assert_eq!(
+            deposit.inputs[0].internal_type,
+            Some(InternalType::Struct {
+                contract: Some("IVault".to_owned()),
+                ty: "Deposit".to_owned(),
+            })
+        );
+        let deposits = &abi.functions["deposits"][0].outputs[0];
+        assert_eq!(deposits.ty, "tuple[]");
+        assert_eq!(deposits.components[0].name, "owner");
+        assert_eq!(
+            abi.functions["balanceOf"][0].signature(),
+            "balanceOf(address)"
+        );
+        assert!(abi.events["Transfer"][0].inputs[0].indexed);
+        assert_eq!(
+            abi.errors["InsufficientBalance"][0].signature(),
+            "InsufficientBalance(address,uint256)"
+        );
+        assert!(abi.constructor.is_none());
+
+        let json = serde_json::to_value(&abi).expect("failed to serialize");
+        let deposit = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|x| x["name"] == "deposit")
+            .unwrap();
+        assert_eq!(deposit["inputs"][0]["type"], "tuple");
+        assert_eq!(
+            deposit["inputs"][0]["internalType"],
+            "struct IVault.Deposit"
+        );
+        assert_eq!(deposit["inputs"][0]["components"][0]["name"], "owner");
+    }
 }
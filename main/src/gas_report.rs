@@ -0,0 +1,322 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Simulates the calls of a scenario file and aggregates their costs per ABI function.
+
+use crate::{
+    trace::{HostioKind, Trace, TraceFrame},
+    util::{abi::encode_call, color::Color},
+    GasReportArgs,
+};
+use alloy::{
+    network::TransactionBuilder,
+    primitives::{Address, U256},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::{state::StateOverride, TransactionRequest},
+};
+use eyre::{bail, eyre, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The calls to make, as read from a JSON scenario file.
+#[derive(Deserialize)]
+struct Scenario {
+    /// The contract to call, unless overridden by a call.
+    to: Option<Address>,
+    calls: Vec<ScenarioCall>,
+}
+
+#[derive(Deserialize)]
+struct ScenarioCall {
+    /// The function signature, e.g. `transfer(address,uint256)`.
+    sig: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    value: U256,
+    from: Option<Address>,
+    to: Option<Address>,
+}
+
+/// Ink spent in hostios, grouped by what they do.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InkByCategory {
+    pub storage: u64,
+    pub calls: u64,
+    pub math: u64,
+    pub keccak: u64,
+    pub other: u64,
+}
+
+impl InkByCategory {
+    fn measure(frame: &TraceFrame) -> Self {
+        let mut ink = Self::default();
+        for step in frame.steps() {
+            let spent = step.start_ink.saturating_sub(step.end_ink);
+            let category = match &step.kind {
+                HostioKind::CallContract { .. }
+                | HostioKind::DelegateCallContract { .. }
+                | HostioKind::StaticCallContract { .. }
+                | HostioKind::Create1 { .. }
+                | HostioKind::Create2 { .. }
+                | HostioKind::ReadReturnData { .. }
+                | HostioKind::ReturnDataSize { .. }
+                | HostioKind::EVMCall { .. } => &mut ink.calls,
+                HostioKind::NativeKeccak256 { .. } => &mut ink.keccak,
+                kind => match kind.name() {
+                    name if name.starts_with("storage_") || name.starts_with("transient_") => {
+                        &mut ink.storage
+                    }
+                    name if name.starts_with("math_") => &mut ink.math,
+                    _ => &mut ink.other,
+                },
+            };
+            *category += spent;
+        }
+        ink
+    }
+
+    fn average(samples: &[Self]) -> Self {
+        let count = samples.len().max(1) as u64;
+        let sum = |field: fn(&Self) -> u64| samples.iter().map(field).sum::<u64>() / count;
+        Self {
+            storage: sum(|x| x.storage),
+            calls: sum(|x| x.calls),
+            math: sum(|x| x.math),
+            keccak: sum(|x| x.keccak),
+            other: sum(|x| x.other),
+        }
+    }
+}
+
+/// The cost of a single simulated call.
+struct Measurement {
+    /// The gas the node estimates for the whole transaction.
+    gas: u64,
+    ink: InkByCategory,
+    return_size: usize,
+}
+
+impl Measurement {
+    fn new(frame: &TraceFrame, gas: u64) -> Self {
+        let return_size = frame
+            .steps()
+            .iter()
+            .rev()
+            .find_map(|step| match &step.kind {
+                HostioKind::WriteResult { result } => Some(result.len()),
+                _ => None,
+            })
+            .unwrap_or_default();
+        Self {
+            gas,
+            ink: InkByCategory::measure(frame),
+            return_size,
+        }
+    }
+}
+
+/// The aggregate cost of the calls to a function.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FunctionReport {
+    pub calls: usize,
+    pub min_gas: u64,
+    pub avg_gas: u64,
+    pub median_gas: u64,
+    pub max_gas: u64,
+    /// The average ink spent in each category of hostio.
+    pub ink: InkByCategory,
+    pub avg_return_size: usize,
+}
+
+impl FunctionReport {
+    fn new(measurements: &[Measurement]) -> Self {
+        let mut gas: Vec<_> = measurements.iter().map(|x| x.gas).collect();
+        gas.sort_unstable();
+        let calls = measurements.len();
+        let ink: Vec<_> = measurements.iter().map(|x| x.ink).collect();
+        Self {
+            calls,
+            min_gas: gas.first().copied().unwrap_or_default(),
+            avg_gas: gas.iter().sum::<u64>() / calls.max(1) as u64,
+            median_gas: gas.get(calls / 2).copied().unwrap_or_default(),
+            max_gas: gas.last().copied().unwrap_or_default(),
+            ink: InkByCategory::average(&ink),
+            avg_return_size: measurements.iter().map(|x| x.return_size).sum::<usize>()
+                / calls.max(1),
+        }
+    }
+}
+
+/// Reports keyed by function signature, so that reports of different commits line up.
+pub type GasReport = BTreeMap<String, FunctionReport>;
+
+pub async fn gas_report(args: &GasReportArgs) -> Result<()> {
+    let scenario = fs::read_to_string(&args.scenario)
+        .wrap_err_with(|| eyre!("failed to read {}", args.scenario.display()))?;
+    let scenario: Scenario = serde_json::from_str(&scenario)
+        .wrap_err_with(|| eyre!("failed to parse {}", args.scenario.display()))?;
+
+    let provider = ProviderBuilder::new().connect(&args.endpoint).await?;
+
+    let mut measurements: BTreeMap<String, Vec<Measurement>> = BTreeMap::new();
+    for call in &scenario.calls {
+        let Some(to) = call.to.or(scenario.to) else {
+            bail!("no contract to call for {}", call.sig);
+        };
+        let mut tx = TransactionRequest::default()
+            .with_to(to)
+            .with_value(call.value)
+            .with_input(encode_call(&call.sig, &call.args)?);
+        if let Some(from) = call.from {
+            tx = tx.with_from(from);
+        }
+        let gas = provider
+            .estimate_gas(tx.clone())
+            .block(args.block)
+            .await
+            .wrap_err_with(|| eyre!("failed to estimate gas for {}", call.sig))?;
+        let trace = Trace::trace_call(
+            &provider,
+            tx,
+            args.block,
+            StateOverride::default(),
+            args.tracer.use_native_tracer,
+        )
+        .await
+        .wrap_err_with(|| eyre!("failed to simulate {}", call.sig))?;
+
+        let measurement = Measurement::new(&trace.top_frame, gas);
+        measurements
+            .entry(call.sig.clone())
+            .or_default()
+            .push(measurement);
+    }
+
+    let report: GasReport = measurements
+        .iter()
+        .map(|(sig, measurements)| (sig.clone(), FunctionReport::new(measurements)))
+        .collect();
+
+    let previous = match &args.compare {
+        Some(path) => Some(load(path)?),
+        None => None,
+    };
+    print_report(&report, previous.as_ref());
+
+    if let Some(path) = &args.save {
+        fs::write(path, serde_json::to_string_pretty(&report)?)
+            .wrap_err_with(|| eyre!("failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<GasReport> {
+    let data =
+        fs::read_to_string(path).wrap_err_with(|| eyre!("failed to read {}", path.display()))?;
+    serde_json::from_str(&data).wrap_err_with(|| eyre!("failed to parse {}", path.display()))
+}
+
+/// Prints the report as a table of transaction gas followed by the ink of each hostio category,
+/// with the change in average gas if given a previous report.
+pub fn print_report(report: &GasReport, previous: Option<&GasReport>) {
+    let width = report.keys().map(|sig| sig.len()).max().unwrap_or(0).max(8);
+    let mut header = format!(
+        "{:<width$} | {:>5} | {:>8} | {:>8} | {:>8} | {:>8} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>6}",
+        "function", "#", "min", "avg", "median", "max", "storage", "calls", "math", "keccak", "other", "return",
+    );
+    if previous.is_some() {
+        header += &format!(" | {:>8}", "Δ avg");
+    }
+    println!("{header}");
+    println!("{}", "-".repeat(header.chars().count()));
+
+    for (sig, entry) in report {
+        let ink = &entry.ink;
+        let mut row = format!(
+            "{sig:<width$} | {:>5} | {:>8} | {:>8} | {:>8} | {:>8} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} | {:>6}",
+            entry.calls,
+            entry.min_gas,
+            entry.avg_gas,
+            entry.median_gas,
+            entry.max_gas,
+            ink.storage,
+            ink.calls,
+            ink.math,
+            ink.keccak,
+            ink.other,
+            entry.avg_return_size,
+        );
+        if let Some(previous) = previous {
+            let delta = match previous.get(sig) {
+                Some(old) => {
+                    let delta = entry.avg_gas as i128 - old.avg_gas as i128;
+                    match delta {
+                        ..0 => format!("{delta:>8}").mint(),
+                        0 => format!("{delta:>8}").grey(),
+                        _ => format!("{:>8}", format!("+{delta}")).red(),
+                    }
+                }
+                None => format!("{:>8}", "new").yellow(),
+            };
+            row += &format!(" | {delta}");
+        }
+        println!("{row}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measure_calls() {
+        let trace = r#"
+        [
+          {
+            "name": "storage_load_bytes32",
+            "args": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "outs": "0x000000000000000000000000000000000000000000000000000000000000002a",
+            "startInk": 100000,
+            "endInk": 80000
+          },
+          {
+            "name": "native_keccak256",
+            "args": "0x00",
+            "outs": "0xbc36789e7a1e281436464229828f817d6612f7b477d66591ff96a9e064bcc98a",
+            "startInk": 70000,
+            "endInk": 65000
+          },
+          {
+            "name": "write_result",
+            "args": "0x2a2a",
+            "outs": "0x",
+            "startInk": 60000,
+            "endInk": 50000
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+
+        let measurement = Measurement::new(&frame, 25_000);
+        assert_eq!(measurement.gas, 25_000);
+        assert_eq!(measurement.return_size, 2);
+        assert_eq!(measurement.ink.storage, 20000);
+        assert_eq!(measurement.ink.keccak, 5000);
+        assert_eq!(measurement.ink.other, 10000);
+
+        let cheap = Measurement {
+            gas: 21_000,
+            ink: InkByCategory::default(),
+            return_size: 0,
+        };
+        let report = FunctionReport::new(&[measurement, cheap]);
+        assert_eq!(report.calls, 2);
+        assert_eq!(
+            (report.min_gas, report.avg_gas, report.max_gas),
+            (21_000, 23_000, 25_000)
+        );
+        assert_eq!(report.ink.storage, 10000);
+        assert_eq!(report.avg_return_size, 1);
+    }
+}
@@ -1,10 +1,17 @@
 // Copyright 2023-2024, Offchain Labs, Inc.
 // For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
 
-use alloy::json_abi::{Function, JsonAbi, StateMutability};
+use crate::storage::StorageType;
+use alloy::json_abi::{Event, Function, JsonAbi, Param, StateMutability};
 use eyre::{bail, Result};
-use serde_json::Value;
-use std::{collections::HashMap, fmt::Write, fs, io::BufReader, path::Path};
+use serde_json::{Map, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+    fs,
+    io::BufReader,
+    path::Path,
+};
 use tiny_keccak::{Hasher, Keccak};
 
 fn c_bytearray_initializer(val: &[u8]) -> String {
@@ -17,6 +24,24 @@ pub fn c_gen(in_path: &Path, out_path: &Path) -> Result<()> {
 
     let input: Value = serde_json::from_reader(BufReader::new(f))?;
 
+    // a plain ABI describes a single contract, named after its file
+    if input.is_array() {
+        let Some(stem) = in_path.file_stem().and_then(|x| x.to_str()) else {
+            bail!("invalid ABI file name {}", in_path.to_string_lossy());
+        };
+        let contract_name = c_identifier(stem);
+        let properties = Map::from_iter([("abi".to_owned(), input)]);
+        let unique_identifier = format!("__{}_", contract_name.to_uppercase());
+        fs::create_dir_all(out_path)?;
+        return gen_contract(
+            out_path,
+            &unique_identifier,
+            &contract_name,
+            &properties,
+            vec![stem],
+        );
+    }
+
     let Some(input_contracts) = input["contracts"].as_object() else {
         bail!(
             "did not find top-level contracts object in {}",
@@ -45,164 +70,222 @@ pub fn c_gen(in_path: &Path, out_path: &Path) -> Result<()> {
                 continue;
             };
 
-            let mut methods: HashMap<String, Vec<Function>> = HashMap::default();
+            let mut unique_identifier = String::from("__");
+            unique_identifier += &c_identifier(solidity_file_name).to_uppercase();
+            unique_identifier += "_";
+            unique_identifier += &contract_name.to_uppercase();
+            unique_identifier += "_";
+
+            gen_contract(
+                &pathbuf,
+                &unique_identifier,
+                contract_name,
+                properties,
+                debug_path,
+            )?;
+        }
+        pathbuf.pop();
+    }
+    Ok(())
+}
 
-            if let Some(raw) = properties.get("abi") {
-                // Sadly, JsonAbi = serde_json::from_value is not supported.
-                // Tonight, we hack!
-                let abi_json = serde_json::to_string(raw)?;
-                let abi: JsonAbi = serde_json::from_str(&abi_json)?;
-                for function in abi.functions() {
-                    let name = function.name.clone();
-                    methods.entry(name).or_default().push(function.clone());
-                }
-            } else {
-                println!("skipping abi for {:?}: not found", &debug_path);
-            }
+/// Replaces the characters of a name that can't appear in a C identifier, such as the dashes of a
+/// file name, with underscores.
+fn c_identifier(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| match c.is_ascii_alphanumeric() {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    if !ident.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        ident.insert(0, '_');
+    }
+    ident
+}
 
-            let mut header = String::default();
-            let mut router = String::default();
-
-            for (simple_name, mut overloads) in methods {
-                overloads.sort_by_key(|a| a.signature());
-
-                for (index, overload) in overloads.iter().enumerate() {
-                    let c_name = match index {
-                        0 => simple_name.clone(),
-                        x => format!("{simple_name}_{x}"),
-                    };
-                    let selector = u32::from_be_bytes(*overload.selector());
-
-                    let (hdr_params, call_params, payable) = match overload.state_mutability {
-                        StateMutability::Pure => {
-                            ("(uint8_t *input, size_t len)", "(input, len)", false)
-                        }
-                        StateMutability::View => (
-                            "(const void *storage, uint8_t *input, size_t len)",
-                            "(NULL, input, len)",
-                            false,
-                        ),
-                        StateMutability::NonPayable => (
-                            "(void *storage, uint8_t *input, size_t len)",
-                            "(NULL, input, len)",
-                            false,
-                        ),
-                        StateMutability::Payable => (
-                            "(void *storage, uint8_t *input, size_t len, bebi32 value)",
-                            "(NULL, input, len, value)",
-                            true,
-                        ),
-                    };
-
-                    let sig = &overload.signature();
-                    writeln!(
-                        header,
-                        "#define SELECTOR_{c_name} 0x{selector:08x} // {sig}"
-                    )?;
-                    writeln!(header, "ArbResult {c_name}{hdr_params}; // {sig}")?;
+/// Generates the header and router of a contract from its ABI and storage layout.
+fn gen_contract(
+    dir: &Path,
+    unique_identifier: &str,
+    contract_name: &str,
+    properties: &Map<String, Value>,
+    mut debug_path: Vec<&str>,
+) -> Result<()> {
+    let mut methods: HashMap<String, Vec<Function>> = HashMap::default();
+
+    let abi: JsonAbi = if let Some(raw) = properties.get("abi") {
+        // Sadly, JsonAbi = serde_json::from_value is not supported.
+        // Tonight, we hack!
+        let abi_json = serde_json::to_string(raw)?;
+        serde_json::from_str(&abi_json)?
+    } else {
+        println!("skipping abi for {:?}: not found", &debug_path);
+        JsonAbi::default()
+    };
+    for function in abi.functions() {
+        let name = function.name.clone();
+        methods.entry(name).or_default().push(function.clone());
+    }
 
-                    writeln!(router, "    if (selector==SELECTOR_{c_name}) {{")?;
-                    if !payable {
-                        writeln!(router, "        if (!bebi32_is_zero(value)) revert();")?;
-                    }
-                    writeln!(router, "        return {c_name}{call_params};\n    }}")?;
-                }
-            }
+    let mut header = String::default();
+    let mut router = String::default();
+    let mut helpers = String::default();
+
+    for (simple_name, mut overloads) in methods {
+        overloads.sort_by_key(|a| a.signature());
+
+        for (index, overload) in overloads.iter().enumerate() {
+            let c_name = match index {
+                0 => simple_name.clone(),
+                x => format!("{simple_name}_{x}"),
+            };
+            let selector = u32::from_be_bytes(*overload.selector());
+
+            let (hdr_params, call_params, payable) = match overload.state_mutability {
+                StateMutability::Pure => ("(uint8_t *input, size_t len)", "(input, len)", false),
+                StateMutability::View => (
+                    "(const void *storage, uint8_t *input, size_t len)",
+                    "(NULL, input, len)",
+                    false,
+                ),
+                StateMutability::NonPayable => (
+                    "(void *storage, uint8_t *input, size_t len)",
+                    "(NULL, input, len)",
+                    false,
+                ),
+                StateMutability::Payable => (
+                    "(void *storage, uint8_t *input, size_t len, bebi32 value)",
+                    "(NULL, input, len, value)",
+                    true,
+                ),
+            };
 
-            if !header.is_empty() {
-                header.push('\n');
+            let sig = &overload.signature();
+            writeln!(
+                header,
+                "#define SELECTOR_{c_name} 0x{selector:08x} // {sig}"
+            )?;
+            writeln!(header, "ArbResult {c_name}{hdr_params}; // {sig}")?;
+            helpers += &function_helpers(&c_name, overload);
+
+            writeln!(router, "    if (selector==SELECTOR_{c_name}) {{")?;
+            if !payable {
+                writeln!(router, "        if (!bebi32_is_zero(value)) revert();")?;
             }
-            debug_path.push("storageLayout");
+            writeln!(router, "        return {c_name}{call_params};\n    }}")?;
+        }
+    }
 
-            if let Some(Value::Object(layout_vals)) = properties.get("storageLayout") {
-                debug_path.push("storage");
+    for (name, overloads) in &abi.events {
+        for (index, event) in overloads.iter().enumerate() {
+            let c_name = match index {
+                0 => name.clone(),
+                x => format!("{name}_{x}"),
+            };
+            helpers += &event_helpers(&c_name, event);
+        }
+    }
 
-                if let Some(Value::Array(storage_arr)) = layout_vals.get("storage") {
-                    for storage_val in storage_arr {
-                        let Some(storage_obj) = storage_val.as_object() else {
-                            println!("skipping output inside {debug_path:?}: not an object..");
-                            continue;
-                        };
-                        let Some(Value::String(label)) = storage_obj.get("label") else {
-                            println!("skipping output inside {debug_path:?}: no label..");
-                            continue;
-                        };
-                        let Some(Value::String(slot)) = storage_obj.get("slot") else {
-                            println!("skipping output inside {debug_path:?}: no slot..");
-                            continue;
-                        };
-                        let Ok(slot) = slot.parse::<u64>() else {
-                            println!("skipping output inside {debug_path:?}: slot not u64..");
-                            continue;
-                        };
-                        let Some(Value::String(val_type)) = storage_obj.get("type") else {
-                            println!("skipping output inside {debug_path:?}: no type..");
-                            continue;
-                        };
-                        let Some(Value::Number(read_offset)) = storage_obj.get("offset") else {
-                            println!("skipping output inside {debug_path:?}: no offset..");
+    if !header.is_empty() {
+        header.push('\n');
+    }
+    debug_path.push("storageLayout");
+
+    let mut accessors = String::default();
+    if let Some(Value::Object(layout_vals)) = properties.get("storageLayout") {
+        let types: BTreeMap<String, StorageType> = match layout_vals.get("types") {
+            Some(types @ Value::Object(_)) => serde_json::from_value(types.clone())?,
+            _ => BTreeMap::new(),
+        };
+        debug_path.push("storage");
+
+        if let Some(Value::Array(storage_arr)) = layout_vals.get("storage") {
+            for storage_val in storage_arr {
+                let Some(storage_obj) = storage_val.as_object() else {
+                    println!("skipping output inside {debug_path:?}: not an object..");
+                    continue;
+                };
+                let Some(Value::String(label)) = storage_obj.get("label") else {
+                    println!("skipping output inside {debug_path:?}: no label..");
+                    continue;
+                };
+                let Some(Value::String(slot)) = storage_obj.get("slot") else {
+                    println!("skipping output inside {debug_path:?}: no slot..");
+                    continue;
+                };
+                let Ok(slot) = slot.parse::<u64>() else {
+                    println!("skipping output inside {debug_path:?}: slot not u64..");
+                    continue;
+                };
+                let Some(Value::String(val_type)) = storage_obj.get("type") else {
+                    println!("skipping output inside {debug_path:?}: no type..");
+                    continue;
+                };
+                let Some(Value::Number(read_offset)) = storage_obj.get("offset") else {
+                    println!("skipping output inside {debug_path:?}: no offset..");
+                    continue;
+                };
+                let offset = match read_offset.as_i64() {
+                    None => {
+                        println!("skipping output inside {debug_path:?}: unexpected offset..",);
+                        continue;
+                    }
+                    Some(num) => {
+                        if !(0..=32).contains(&num) {
+                            println!("skipping output inside {debug_path:?}: unexpected offset..",);
                             continue;
                         };
-                        let offset = match read_offset.as_i64() {
-                            None => {
-                                println!(
-                                    "skipping output inside {debug_path:?}: unexpected offset..",
-                                );
-                                continue;
-                            }
-                            Some(num) => {
-                                if !(0..=32).contains(&num) {
-                                    println!(
-                                        "skipping output inside {debug_path:?}: unexpected offset..",
-                                    );
-                                    continue;
-                                };
-                                32 - num
-                            }
-                        };
-                        let mut slot_buf = vec![0u8; 32 - 8];
-                        slot_buf.extend(slot.to_be_bytes());
-
+                        32 - num
+                    }
+                };
+                let mut slot_buf = vec![0u8; 32 - 8];
+                slot_buf.extend(slot.to_be_bytes());
+
+                writeln!(
+                    header,
+                    "#define STORAGE_SLOT_{label} {} // {val_type}",
+                    c_bytearray_initializer(&slot_buf),
+                )?;
+                if val_type.starts_with("t_array(") {
+                    if val_type.ends_with(")dyn_storage") {
+                        let mut keccak = Keccak::v256();
+                        keccak.update(&slot_buf);
+                        keccak.finalize(&mut slot_buf);
                         writeln!(
                             header,
-                            "#define STORAGE_SLOT_{label} {} // {val_type}",
+                            "#define STORAGE_BASE_{label} {} // {val_type}",
                             c_bytearray_initializer(&slot_buf),
                         )?;
-                        if val_type.starts_with("t_array(") {
-                            if val_type.ends_with(")dyn_storage") {
-                                let mut keccak = Keccak::v256();
-                                keccak.update(&slot_buf);
-                                keccak.finalize(&mut slot_buf);
-                                writeln!(
-                                    header,
-                                    "#define STORAGE_BASE_{label} {} // {val_type}",
-                                    c_bytearray_initializer(&slot_buf),
-                                )?;
-                            }
-                        } else if !val_type.starts_with("t_mapping") {
-                            writeln!(
-                                header,
-                                "#define STORAGE_END_OFFSET_{label} {offset} // {val_type}",
-                            )?;
-                        }
                     }
-                } else {
-                    println!("skipping output for {debug_path:?}: not an array..");
+                } else if !val_type.starts_with("t_mapping") {
+                    writeln!(
+                        header,
+                        "#define STORAGE_END_OFFSET_{label} {offset} // {val_type}",
+                    )?;
                 }
-                debug_path.pop();
-            } else {
-                println!("skipping output for {:?}: not an object..", &debug_path);
+                accessors += &storage_accessors(label, val_type, offset as usize, &types);
             }
-            debug_path.pop();
-            if !header.is_empty() {
-                let mut unique_identifier = String::from("__");
-                unique_identifier += &solidity_file_name.to_uppercase();
-                unique_identifier += "_";
-                unique_identifier += &contract_name.to_uppercase();
-                unique_identifier += "_";
-
-                let contents = format!(
-                    r#" // autogenerated by cargo-stylus
+        } else {
+            println!("skipping output for {debug_path:?}: not an array..");
+        }
+        debug_path.pop();
+    } else if properties.contains_key("storageLayout") {
+        println!("skipping output for {:?}: not an object..", &debug_path);
+    }
+    debug_path.pop();
+    if !helpers.is_empty() || !accessors.is_empty() {
+        header += ABI_HELPERS;
+    }
+    if !accessors.is_empty() {
+        header += STORAGE_HELPERS;
+    }
+    header += &helpers;
+    header += &accessors;
+    if !header.is_empty() {
+        let contents = format!(
+            r#" // autogenerated by cargo-stylus
 #ifndef {uniq}
 #define {uniq}
 
@@ -223,18 +306,15 @@ ArbResult default_func(void *storage, uint8_t *input, size_t len, bebi32 value);
 
 #endif // {uniq}
 "#,
-                    uniq = unique_identifier,
-                    body = header
-                );
+            uniq = unique_identifier,
+            body = header
+        );
 
-                let filename: String = contract_name.into();
-                pathbuf.push(filename + ".h");
-                fs::write(&pathbuf, &contents)?;
-                pathbuf.pop();
-            }
-            if !router.is_empty() {
-                let contents = format!(
-                    r#" // autogenerated by cargo-stylus
+        fs::write(dir.join(format!("{contract_name}.h")), &contents)?;
+    }
+    if !router.is_empty() {
+        let contents = format!(
+            r#" // autogenerated by cargo-stylus
 
 #include "{contract}.h"
 #include <stylus_types.h>
@@ -259,17 +339,631 @@ ArbResult {contract}_entry(uint8_t *input, size_t len) {{
 
 ENTRYPOINT({contract}_entry)
 "#,
-                    contract = contract_name,
-                    body = router
-                );
+            contract = contract_name,
+            body = router
+        );
+
+        fs::write(dir.join(format!("{contract_name}_main.c")), &contents)?;
+    }
+    Ok(())
+}
+
+/// Primitives for reading and writing the words of static ABI values, used by the helpers
+/// generated for each function and event. Guarded so that headers of several contracts can be
+/// included together.
+const ABI_HELPERS: &str = r#"
+#ifndef STYLUS_ABI_HELPERS
+#define STYLUS_ABI_HELPERS
+
+#include <hostio.h>
+
+static inline bool abi_check_uint(const uint8_t *word, size_t size) {
+    for (size_t i = 0; i < 32 - size; i++) {
+        if (word[i] != 0) return false;
+    }
+    return true;
+}
+
+static inline bool abi_check_int(const uint8_t *word, size_t size) {
+    uint8_t fill = (word[32 - size] & 0x80) ? 0xff : 0;
+    for (size_t i = 0; i < 32 - size; i++) {
+        if (word[i] != fill) return false;
+    }
+    return true;
+}
+
+static inline bool abi_check_bytes(const uint8_t *word, size_t size) {
+    for (size_t i = size; i < 32; i++) {
+        if (word[i] != 0) return false;
+    }
+    return true;
+}
+
+static inline uint64_t abi_get_u64(const uint8_t *word) {
+    uint64_t value = 0;
+    for (size_t i = 24; i < 32; i++) {
+        value = (value << 8) | word[i];
+    }
+    return value;
+}
+
+static inline void abi_set_u64(uint8_t *word, uint64_t value) {
+    for (size_t i = 0; i < 24; i++) {
+        word[i] = 0;
+    }
+    for (size_t i = 32; i > 24; i--) {
+        word[i - 1] = (uint8_t)value;
+        value >>= 8;
+    }
+}
+
+static inline void abi_set_i64(uint8_t *word, int64_t value) {
+    abi_set_u64(word, (uint64_t)value);
+    if (value < 0) {
+        for (size_t i = 0; i < 24; i++) {
+            word[i] = 0xff;
+        }
+    }
+}
+
+static inline void abi_copy(uint8_t *dest, const uint8_t *src, size_t len) {
+    for (size_t i = 0; i < len; i++) {
+        dest[i] = src[i];
+    }
+}
+
+#endif // STYLUS_ABI_HELPERS
+"#;
+
+/// Primitives for locating and packing storage values, used by the generated accessors, guarded
+/// like [`ABI_HELPERS`].
+const STORAGE_HELPERS: &str = r#"
+#ifndef STYLUS_STORAGE_HELPERS
+#define STYLUS_STORAGE_HELPERS
+
+static inline void storage_mapping_slot(uint8_t *slot, const uint8_t *key) {
+    uint8_t preimage[64];
+    abi_copy(preimage, key, 32);
+    abi_copy(preimage + 32, slot, 32);
+    native_keccak256(preimage, 64, slot);
+}
+
+static inline void storage_array_base(uint8_t *slot) {
+    uint8_t preimage[32];
+    abi_copy(preimage, slot, 32);
+    native_keccak256(preimage, 32, slot);
+}
+
+static inline void storage_slot_add(uint8_t *slot, uint64_t value) {
+    for (size_t i = 32; i > 0 && value != 0; i--) {
+        uint64_t sum = slot[i - 1] + (value & 0xff);
+        slot[i - 1] = (uint8_t)sum;
+        value = (value >> 8) + (sum >> 8);
+    }
+}
 
-                let filename: String = contract_name.into();
-                pathbuf.push(filename + "_main.c");
-                fs::write(&pathbuf, &contents)?;
-                pathbuf.pop();
+static inline uint64_t storage_get_u64(const uint8_t *word, size_t end, size_t size) {
+    uint64_t value = 0;
+    for (size_t i = end - size; i < end; i++) {
+        value = (value << 8) | word[i];
+    }
+    return value;
+}
+
+static inline int64_t storage_get_i64(const uint8_t *word, size_t end, size_t size) {
+    size_t shift = 64 - 8 * size;
+    return (int64_t)(storage_get_u64(word, end, size) << shift) >> shift;
+}
+
+static inline void storage_set_u64(uint8_t *word, size_t end, size_t size, uint64_t value) {
+    for (size_t i = end; i > end - size; i--) {
+        word[i - 1] = (uint8_t)value;
+        value >>= 8;
+    }
+}
+
+static inline void storage_get_word(const uint8_t *word, size_t end, size_t size, bool is_signed, uint8_t *value) {
+    uint8_t fill = (is_signed && (word[end - size] & 0x80)) ? 0xff : 0;
+    for (size_t i = 0; i < 32 - size; i++) {
+        value[i] = fill;
+    }
+    abi_copy(value + 32 - size, word + end - size, size);
+}
+
+#endif // STYLUS_STORAGE_HELPERS
+"#;
+
+/// An ABI type encoded in a single word, which is what the generated helpers support.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum StaticType {
+    Uint(usize),
+    Int(usize),
+    Bool,
+    Address,
+    FixedBytes(usize),
+}
+
+impl StaticType {
+    fn parse(ty: &str) -> Option<Self> {
+        let size = |x: &str| x.parse::<usize>().ok();
+        Some(match ty {
+            "bool" => Self::Bool,
+            "address" => Self::Address,
+            _ if ty.starts_with("uint") => Self::Uint(size(&ty[4..])? / 8),
+            _ if ty.starts_with("int") => Self::Int(size(&ty[3..])? / 8),
+            _ if ty.starts_with("bytes") => Self::FixedBytes(size(&ty[5..])?),
+            _ => return None,
+        })
+    }
+
+    /// The integer type holding a value of up to 8 bytes.
+    fn c_int(signed: bool, size: usize) -> String {
+        let bits = size.next_power_of_two() * 8;
+        match signed {
+            true => format!("int{bits}_t"),
+            false => format!("uint{bits}_t"),
+        }
+    }
+
+    /// Declares a struct field holding the value.
+    fn field(self, name: &str) -> String {
+        match self {
+            Self::Uint(size) if size <= 8 => format!("{} {name};", Self::c_int(false, size)),
+            Self::Int(size) if size <= 8 => format!("{} {name};", Self::c_int(true, size)),
+            Self::Uint(_) | Self::Int(_) => format!("bebi32 {name};"),
+            Self::Bool => format!("bool {name};"),
+            Self::Address => format!("uint8_t {name}[20];"),
+            Self::FixedBytes(size) => format!("uint8_t {name}[{size}];"),
+        }
+    }
+
+    /// The number of bytes the value takes in storage.
+    fn size(self) -> usize {
+        match self {
+            Self::Uint(size) | Self::Int(size) | Self::FixedBytes(size) => size,
+            Self::Bool => 1,
+            Self::Address => 20,
+        }
+    }
+
+    /// Declares a parameter passing the value in.
+    fn param(self, name: &str) -> String {
+        match self {
+            Self::Uint(size) if size <= 8 => format!("{} {name}", Self::c_int(false, size)),
+            Self::Int(size) if size <= 8 => format!("{} {name}", Self::c_int(true, size)),
+            Self::Uint(_) | Self::Int(_) => format!("const bebi32 {name}"),
+            Self::Bool => format!("bool {name}"),
+            Self::Address => format!("const uint8_t {name}[20]"),
+            Self::FixedBytes(size) => format!("const uint8_t {name}[{size}]"),
+        }
+    }
+
+    /// Declares a parameter the value is written out to.
+    fn out_param(self, name: &str) -> String {
+        match self {
+            Self::Uint(size) if size <= 8 => format!("{} *{name}", Self::c_int(false, size)),
+            Self::Int(size) if size <= 8 => format!("{} *{name}", Self::c_int(true, size)),
+            Self::Uint(_) | Self::Int(_) => format!("bebi32 {name}"),
+            Self::Bool => format!("bool *{name}"),
+            Self::Address => format!("uint8_t {name}[20]"),
+            Self::FixedBytes(size) => format!("uint8_t {name}[{size}]"),
+        }
+    }
+
+    /// A statement reading the value packed in a storage `word` before `end` into `dest`.
+    fn load(self, dest: &str) -> String {
+        let size = self.size();
+        match self {
+            Self::Uint(_) if size <= 8 => format!(
+                "*{dest} = ({})storage_get_u64(word, end, {size});",
+                Self::c_int(false, size)
+            ),
+            Self::Int(_) if size <= 8 => format!(
+                "*{dest} = ({})storage_get_i64(word, end, {size});",
+                Self::c_int(true, size)
+            ),
+            Self::Uint(_) => format!("storage_get_word(word, end, {size}, false, {dest});"),
+            Self::Int(_) => format!("storage_get_word(word, end, {size}, true, {dest});"),
+            Self::Bool => format!("*{dest} = word[end - 1] != 0;"),
+            Self::Address | Self::FixedBytes(_) => {
+                format!("abi_copy({dest}, word + end - {size}, {size});")
             }
         }
-        pathbuf.pop();
     }
-    Ok(())
+
+    /// A statement packing `src` into a storage `word` before `end`.
+    fn store(self, src: &str) -> String {
+        let size = self.size();
+        match self {
+            Self::Uint(_) | Self::Int(_) if size <= 8 => {
+                format!("storage_set_u64(word, end, {size}, (uint64_t){src});")
+            }
+            Self::Uint(_) | Self::Int(_) => {
+                format!(
+                    "abi_copy(word + end - {size}, {src} + {}, {size});",
+                    32 - size
+                )
+            }
+            Self::Bool => format!("word[end - 1] = {src} ? 1 : 0;"),
+            Self::Address | Self::FixedBytes(_) => {
+                format!("abi_copy(word + end - {size}, {src}, {size});")
+            }
+        }
+    }
+
+    /// Statements validating the word and decoding it into `dest`, returning false if invalid.
+    fn decode(self, word: &str, dest: &str) -> String {
+        match self {
+            Self::Uint(size) if size <= 8 => format!(
+                "if (!abi_check_uint({word}, {size})) return false;\n    {dest} = ({})abi_get_u64({word});",
+                Self::c_int(false, size)
+            ),
+            Self::Int(size) if size <= 8 => format!(
+                "if (!abi_check_int({word}, {size})) return false;\n    {dest} = ({})(int64_t)abi_get_u64({word});",
+                Self::c_int(true, size)
+            ),
+            Self::Uint(size) => format!(
+                "if (!abi_check_uint({word}, {size})) return false;\n    abi_copy({dest}, {word}, 32);"
+            ),
+            Self::Int(size) => format!(
+                "if (!abi_check_int({word}, {size})) return false;\n    abi_copy({dest}, {word}, 32);"
+            ),
+            Self::Bool => format!(
+                "if (!abi_check_uint({word}, 1) || {word}[31] > 1) return false;\n    {dest} = {word}[31];"
+            ),
+            Self::Address => format!(
+                "if (!abi_check_uint({word}, 20)) return false;\n    abi_copy({dest}, {word} + 12, 20);"
+            ),
+            Self::FixedBytes(size) => format!(
+                "if (!abi_check_bytes({word}, {size})) return false;\n    abi_copy({dest}, {word}, {size});"
+            ),
+        }
+    }
+
+    /// Statements encoding `src` into the word.
+    fn encode(self, word: &str, src: &str) -> String {
+        match self {
+            Self::Uint(size) if size <= 8 => format!("abi_set_u64({word}, {src});"),
+            Self::Int(size) if size <= 8 => format!("abi_set_i64({word}, {src});"),
+            Self::Uint(_) | Self::Int(_) => format!("abi_copy({word}, {src}, 32);"),
+            Self::Bool => format!("abi_set_u64({word}, {src} ? 1 : 0);"),
+            Self::Address => {
+                format!("abi_set_u64({word}, 0);\n    abi_copy({word} + 12, {src}, 20);")
+            }
+            Self::FixedBytes(size) => {
+                format!("abi_set_u64({word}, 0);\n    abi_copy({word}, {src}, {size});")
+            }
+        }
+    }
+}
+
+/// The static types and field names of parameters, or `None` if any of them is dynamic.
+fn static_params<'a>(
+    params: impl IntoIterator<Item = (&'a str, &'a str)>,
+    prefix: &str,
+) -> Option<Vec<(StaticType, String)>> {
+    params
+        .into_iter()
+        .enumerate()
+        .map(|(index, (ty, name))| {
+            let name = match name.is_empty() {
+                true => format!("{prefix}{index}"),
+                false => name.to_owned(),
+            };
+            Some((StaticType::parse(ty)?, name))
+        })
+        .collect()
+}
+
+fn param_types(params: &[Param]) -> impl Iterator<Item = (&str, &str)> {
+    params.iter().map(|x| (x.ty.as_str(), x.name.as_str()))
+}
+
+fn c_struct(name: &str, fields: &[(StaticType, String)]) -> String {
+    let fields: Vec<_> = fields
+        .iter()
+        .map(|(ty, name)| format!("    {}\n", ty.field(name)))
+        .collect();
+    format!("typedef struct {{\n{}}} {name};\n", fields.concat())
+}
+
+/// Structs for the arguments and results of a function, with helpers to decode its arguments and
+/// encode its results. Empty if the function has dynamic parameters.
+fn function_helpers(c_name: &str, function: &Function) -> String {
+    let inputs = static_params(param_types(&function.inputs), "arg");
+    let outputs = static_params(param_types(&function.outputs), "ret");
+    let (Some(inputs), Some(outputs)) = (inputs, outputs) else {
+        return String::new();
+    };
+    if inputs.is_empty() && outputs.is_empty() {
+        return String::new();
+    }
+
+    let mut code = format!("\n// {}\n", function.signature());
+    if !inputs.is_empty() {
+        code += &c_struct(&format!("{c_name}_args"), &inputs);
+        code += &format!(
+            "\nstatic inline bool {c_name}_decode_args(const uint8_t *input, size_t len, {c_name}_args *args) {{\n    if (len < {}) return false;\n",
+            inputs.len() * 32
+        );
+        for (index, (ty, name)) in inputs.iter().enumerate() {
+            let word = format!("input + {}", index * 32);
+            code += &format!("    {}\n", ty.decode(&word, &format!("args->{name}")));
+        }
+        code += "    return true;\n}\n";
+    }
+    if !outputs.is_empty() {
+        code += &c_struct(&format!("{c_name}_result"), &outputs);
+        code += &format!(
+            "\n#define RESULT_LEN_{c_name} {}\n\nstatic inline void {c_name}_encode_result(const {c_name}_result *result, uint8_t *output) {{\n",
+            outputs.len() * 32
+        );
+        for (index, (ty, name)) in outputs.iter().enumerate() {
+            let word = format!("output + {}", index * 32);
+            code += &format!("    {}\n", ty.encode(&word, &format!("result->{name}")));
+        }
+        code += "}\n";
+    }
+    code
+}
+
+/// A struct for the fields of an event, with a helper emitting it with its topics. Empty if the
+/// event has dynamic fields.
+fn event_helpers(c_name: &str, event: &Event) -> String {
+    let params = event
+        .inputs
+        .iter()
+        .map(|x| (x.ty.as_str(), x.name.as_str()));
+    let Some(fields) = static_params(params, "field") else {
+        return String::new();
+    };
+    let (indexed, body): (Vec<_>, Vec<_>) = fields
+        .iter()
+        .zip(&event.inputs)
+        .partition(|(_, input)| input.indexed);
+    let first = usize::from(!event.anonymous);
+    let topics = first + indexed.len();
+    let words = topics + body.len();
+    if words == 0 {
+        return String::new();
+    }
+
+    let mut code = format!("\n// {}\n", event.signature());
+    let mut emit = String::new();
+    if !event.anonymous {
+        code += &format!(
+            "#define TOPIC_{c_name} {}\n",
+            c_bytearray_initializer(event.selector().as_slice())
+        );
+        emit += &format!(
+            "    const uint8_t topic[32] = TOPIC_{c_name};\n    abi_copy(log, topic, 32);\n"
+        );
+    }
+    // indexed fields are topics, which precede the data in the buffer passed to `emit_log`
+    for (index, ((ty, name), _)) in indexed.iter().chain(&body).enumerate() {
+        let word = format!("log + {}", (first + index) * 32);
+        emit += &format!("    {}\n", ty.encode(&word, &format!("event->{name}")));
+    }
+
+    let params = match fields.is_empty() {
+        true => "void".to_owned(),
+        false => {
+            code += &c_struct(&format!("{c_name}_event"), &fields);
+            format!("const {c_name}_event *event")
+        }
+    };
+    code += &format!(
+        "\nstatic inline void emit_{c_name}({params}) {{\n    uint8_t log[{}];\n{emit}    emit_log(log, sizeof(log), {topics});\n}}\n",
+        words * 32
+    );
+    code
+}
+
+/// A function locating a storage variable, taking the keys of its mappings and the indices of its
+/// arrays, with functions loading and storing it if it's a value type. Empty if the variable has
+/// neither, or is keyed by a type that isn't a value type.
+fn storage_accessors(
+    label: &str,
+    ty: &str,
+    end: usize,
+    types: &BTreeMap<String, StorageType>,
+) -> String {
+    let mut params = vec![];
+    let mut args = vec![];
+    let mut steps = String::new();
+    let mut ty = types.get(ty);
+    while let Some(current) = ty {
+        match current.encoding.as_str() {
+            "mapping" => {
+                let key = current.key.as_ref().and_then(|x| types.get(x));
+                let Some(key) = key.and_then(|x| StaticType::parse(&x.label)) else {
+                    return String::new();
+                };
+                let name = format!("key{}", args.len());
+                steps += &format!(
+                    "    {{\n        uint8_t key[32];\n        {}\n        storage_mapping_slot(slot, key);\n    }}\n",
+                    key.encode("key", &name).replace("\n    ", "\n        ")
+                );
+                params.push(key.param(&name));
+                args.push(name);
+                ty = current.value.as_ref().and_then(|x| types.get(x));
+            }
+            "dynamic_array" | "inplace" if current.base.is_some() => {
+                let base = current.base.as_ref().and_then(|x| types.get(x));
+                let Some(size) = base.and_then(|x| x.size().ok()) else {
+                    return String::new();
+                };
+                let name = format!("index{}", args.len());
+                if current.encoding == "dynamic_array" {
+                    steps += "    storage_array_base(slot);\n";
+                }
+                // elements pack into slots like other values
+                match size {
+                    1..=32 => {
+                        let per_slot = 32 / size;
+                        steps += &format!(
+                            "    storage_slot_add(slot, {name} / {per_slot});\n    end = 32 - ({name} % {per_slot}) * {size};\n"
+                        );
+                    }
+                    _ => {
+                        steps += &format!(
+                            "    storage_slot_add(slot, {name} * {});\n    end = 32;\n",
+                            size.div_ceil(32)
+                        );
+                    }
+                }
+                params.push(format!("uint64_t {name}"));
+                args.push(name);
+                ty = base;
+            }
+            _ => break,
+        }
+    }
+
+    let value = ty
+        .filter(|x| x.encoding == "inplace" && x.members.is_none() && x.base.is_none())
+        .and_then(|x| StaticType::parse(&x.label));
+    if params.is_empty() && value.is_none() {
+        return String::new();
+    }
+
+    let with = |extra: String| -> String {
+        let mut all = params.clone();
+        all.push(extra);
+        all.join(", ")
+    };
+    let mut code = format!(
+        "\nstatic inline size_t storage_slot_{label}({}) {{\n    const uint8_t root[32] = STORAGE_SLOT_{label};\n    abi_copy(slot, root, 32);\n    size_t end = {end};\n{steps}    return end;\n}}\n",
+        with("uint8_t *slot".into())
+    );
+    let Some(value) = value else {
+        return code;
+    };
+    let mut call = args.clone();
+    call.push("slot".into());
+    let locate = format!(
+        "    uint8_t slot[32];\n    size_t end = storage_slot_{label}({});\n    uint8_t word[32];\n    storage_load_bytes32(slot, word);\n",
+        call.join(", ")
+    );
+    code += &format!(
+        "\nstatic inline void storage_load_{label}({}) {{\n{locate}    {}\n}}\n",
+        with(value.out_param("value")),
+        value.load("value"),
+    );
+    code += &format!(
+        "\nstatic inline void storage_store_{label}({}) {{\n{locate}    {}\n    storage_cache_bytes32(slot, word);\n}}\n",
+        with(value.param("value")),
+        value.store("value"),
+    );
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_abi_helpers() {
+        let abi = JsonAbi::parse([
+            "function transfer(address to, uint256 value) external returns (bool)",
+            "function setFee(int24 fee, bytes4) external",
+            "function name() external view returns (string)",
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+        ])
+        .unwrap();
+
+        let transfer = function_helpers("transfer", &abi.functions["transfer"][0]);
+        assert!(transfer.contains("    uint8_t to[20];\n    bebi32 value;\n} transfer_args;"));
+        assert!(transfer.contains("if (len < 64) return false;"));
+        assert!(transfer.contains("abi_copy(args->to, input + 0 + 12, 20);"));
+        assert!(transfer.contains("#define RESULT_LEN_transfer 32"));
+        assert!(transfer.contains("abi_set_u64(output + 0, result->ret0 ? 1 : 0);"));
+
+        let set_fee = function_helpers("setFee", &abi.functions["setFee"][0]);
+        assert!(set_fee.contains("    int32_t fee;\n    uint8_t arg1[4];\n} setFee_args;"));
+        assert!(set_fee.contains("if (!abi_check_int(input + 0, 3)) return false;"));
+        assert!(!set_fee.contains("setFee_result"));
+        assert!(function_helpers("name", &abi.functions["name"][0]).is_empty());
+
+        let transfer = event_helpers("Transfer", &abi.events["Transfer"][0]);
+        assert!(transfer.contains("#define TOPIC_Transfer {0xdd, 0xf2, 0x52, 0xad"));
+        assert!(transfer.contains("uint8_t log[128];"));
+        assert!(transfer.contains("abi_copy(log + 96, event->value, 32);"));
+        assert!(transfer.contains("emit_log(log, sizeof(log), 3);"));
+    }
+
+    #[test]
+    fn generate_from_plain_abi() {
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let abi = dir.path().join("my-token.json");
+        let json = r#"[{"type": "function", "name": "totalSupply", "inputs": [], "outputs": [{"name": "", "type": "uint256"}], "stateMutability": "view"}]"#;
+        fs::write(&abi, json).expect("failed to write abi");
+
+        let out = dir.path().join("out");
+        c_gen(&abi, &out).expect("failed to generate");
+        let header = fs::read_to_string(out.join("my_token.h")).expect("missing header");
+        assert!(header.contains("#ifndef __MY_TOKEN_"));
+        let main = fs::read_to_string(out.join("my_token_main.c")).expect("missing router");
+        assert!(main.contains("ArbResult my_token_entry("));
+        // shared helpers are guarded so that several generated headers can be included together
+        assert!(header.contains("#ifndef STYLUS_ABI_HELPERS"));
+        assert_eq!(c_identifier("2fast.sol"), "_2fast_sol");
+    }
+
+    #[test]
+    fn generate_storage_accessors() {
+        let types: BTreeMap<String, StorageType> = serde_json::from_value(serde_json::json!({
+            "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+            "t_uint64": { "encoding": "inplace", "label": "uint64", "numberOfBytes": "8" },
+            "t_string_storage": { "encoding": "bytes", "label": "string", "numberOfBytes": "32" },
+            "t_mapping(t_address,t_uint64)": {
+                "encoding": "mapping", "label": "mapping(address => uint64)",
+                "numberOfBytes": "32", "key": "t_address", "value": "t_uint64"
+            },
+            "t_mapping(t_address,t_mapping(t_address,t_uint64))": {
+                "encoding": "mapping", "label": "mapping(address => mapping(address => uint64))",
+                "numberOfBytes": "32", "key": "t_address",
+                "value": "t_mapping(t_address,t_uint64)"
+            },
+            "t_mapping(t_string_storage,t_uint64)": {
+                "encoding": "mapping", "label": "mapping(string => uint64)",
+                "numberOfBytes": "32", "key": "t_string_storage", "value": "t_uint64"
+            },
+            "t_array(t_uint64)dyn_storage": {
+                "encoding": "dynamic_array", "label": "uint64[]", "numberOfBytes": "32",
+                "base": "t_uint64"
+            },
+        }))
+        .unwrap();
+
+        let allowances = storage_accessors(
+            "allowances",
+            "t_mapping(t_address,t_mapping(t_address,t_uint64))",
+            32,
+            &types,
+        );
+        assert!(allowances.contains("static inline size_t storage_slot_allowances(const uint8_t key0[20], const uint8_t key1[20], uint8_t *slot) {"));
+        assert_eq!(
+            allowances
+                .matches("storage_mapping_slot(slot, key);")
+                .count(),
+            2
+        );
+        assert!(allowances.contains("abi_copy(key + 12, key1, 20);"));
+        assert!(allowances.contains("static inline void storage_load_allowances(const uint8_t key0[20], const uint8_t key1[20], uint64_t *value) {"));
+        assert!(allowances.contains("storage_set_u64(word, end, 8, (uint64_t)value);"));
+        assert!(allowances.contains("storage_cache_bytes32(slot, word);"));
+
+        let history = storage_accessors("history", "t_array(t_uint64)dyn_storage", 32, &types);
+        assert!(history.contains("storage_array_base(slot);"));
+        assert!(history.contains("storage_slot_add(slot, index0 / 4);"));
+        assert!(history.contains("end = 32 - (index0 % 4) * 8;"));
+
+        let owner = storage_accessors("owner", "t_address", 20, &types);
+        assert!(owner.contains("size_t end = 20;"));
+        assert!(owner.contains("abi_copy(value, word + end - 20, 20);"));
+
+        let names = storage_accessors("names", "t_mapping(t_string_storage,t_uint64)", 32, &types);
+        assert!(names.is_empty());
+    }
 }
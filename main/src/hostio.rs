@@ -20,7 +20,10 @@ lazy_static! {
 
 macro_rules! frame {
     ($dec:pat) => {
-        let hostio = FRAME.lock().as_mut().unwrap().next_hostio(function_name!());
+        frame!(function_name!(), $dec)
+    };
+    ($name:expr, $dec:pat) => {
+        let hostio = FRAME.lock().as_mut().unwrap().next_hostio($name);
         *START_INK.lock() = hostio.start_ink;
         *END_INK.lock() = hostio.end_ink;
 
@@ -28,6 +31,22 @@ macro_rules! frame {
     };
 }
 
+macro_rules! check {
+    ($name:ident, $local:expr, $onchain:expr) => {
+        let (local, onchain) = (&$local, &$onchain);
+        if local != onchain
+            && !FRAME.lock().as_mut().unwrap().argument_mismatch(
+                function_name!(),
+                stringify!($name),
+                format!("{local:?}"),
+                format!("{onchain:?}"),
+            )
+        {
+            panic!();
+        }
+    };
+}
+
 macro_rules! copy {
     ($src:expr, $dest:expr) => {
         memcpy($src.as_ptr(), $dest, mem::size_of_val(&$src))
@@ -58,7 +77,7 @@ static READ_ARGS: unsafe extern "C" fn(dest: *mut u8) = read_args;
 #[no_mangle]
 pub unsafe extern "C" fn write_result(data: *const u8, len: u32) {
     frame!(WriteResult { result });
-    assert_eq!(read_bytes(data, len), &*result);
+    check!(result, read_bytes(data, len), &*result);
 }
 
 #[used]
@@ -92,7 +111,7 @@ static EXIT_EARLY: unsafe extern "C" fn(status: u32) = exit_early;
 #[no_mangle]
 pub unsafe extern "C" fn storage_load_bytes32(key_ptr: *const u8, dest: *mut u8) {
     frame!(StorageLoadBytes32 { key, value });
-    assert_eq!(read_fixed(key_ptr), key);
+    check!(key, read_fixed(key_ptr), key);
     copy!(value, dest);
 }
 
@@ -115,8 +134,8 @@ static STORAGE_LOAD_BYTES32: unsafe extern "C" fn(key_ptr: *const u8, dest: *mut
 #[no_mangle]
 pub unsafe extern "C" fn storage_cache_bytes32(key_ptr: *const u8, value_ptr: *const u8) {
     frame!(StorageCacheBytes32 { key, value });
-    assert_eq!(read_fixed(key_ptr), key);
-    assert_eq!(read_fixed(value_ptr), value);
+    check!(key, read_fixed(key_ptr), key);
+    check!(value, read_fixed(value_ptr), value);
 }
 
 #[used]
@@ -146,7 +165,7 @@ static STORAGE_FLUSH_CACHE: unsafe extern "C" fn(clear: u32) = storage_flush_cac
 #[no_mangle]
 pub unsafe extern "C" fn transient_load_bytes32(key_ptr: *const u8, dest: *mut u8) {
     frame!(TransientLoadBytes32 { key, value });
-    assert_eq!(read_fixed(key_ptr), key);
+    check!(key, read_fixed(key_ptr), key);
     copy!(value, dest);
 }
 
@@ -164,8 +183,8 @@ static TRANSIENT_LOAD_BYTES32: unsafe extern "C" fn(key_ptr: *const u8, dest: *m
 #[no_mangle]
 pub unsafe extern "C" fn transient_store_bytes32(key_ptr: *const u8, value_ptr: *const u8) {
     frame!(TransientStoreBytes32 { key, value });
-    assert_eq!(read_fixed(key_ptr), key);
-    assert_eq!(read_fixed(value_ptr), value);
+    check!(key, read_fixed(key_ptr), key);
+    check!(value, read_fixed(value_ptr), value);
 }
 
 #[used]
@@ -180,7 +199,7 @@ static TRANSIENT_STORE_BYTES32: unsafe extern "C" fn(key_ptr: *const u8, value_p
 #[no_mangle]
 pub unsafe extern "C" fn account_balance(address_ptr: *const u8, dest: *mut u8) {
     frame!(AccountBalance { address, balance });
-    assert_eq!(read_fixed(address_ptr), address);
+    check!(address, read_fixed(address_ptr), address);
     copy!(balance.to_be_bytes::<32>(), dest);
 }
 
@@ -208,10 +227,10 @@ pub unsafe extern "C" fn account_code(
         size,
         code
     });
-    assert_eq!(offset_recv, offset);
-    assert_eq!(size_recv, size);
-    assert_eq!(read_fixed(address_ptr), address);
-    copy!(code, dest);
+    check!(offset, offset_recv, offset);
+    check!(size, size_recv, size);
+    check!(address, read_fixed(address_ptr), address);
+    copy!(code, dest, code.len());
     code.len() as u32
 }
 
@@ -231,7 +250,7 @@ static ACCOUNT_CODE: unsafe extern "C" fn(
 #[no_mangle]
 pub unsafe extern "C" fn account_code_size(address_ptr: *const u8) -> u32 {
     frame!(AccountCodeSize { address, size });
-    assert_eq!(read_fixed(address_ptr), address);
+    check!(address, read_fixed(address_ptr), address);
     size
 }
 
@@ -248,7 +267,7 @@ static ACCOUNT_CODE_SIZE: unsafe extern "C" fn(address_ptr: *const u8) -> u32 =
 #[no_mangle]
 pub unsafe extern "C" fn account_codehash(address_ptr: *const u8, dest: *mut u8) {
     frame!(AccountCodehash { address, codehash });
-    assert_eq!(read_fixed(address_ptr), address);
+    check!(address, read_fixed(address_ptr), address);
     copy!(codehash, dest);
 }
 
@@ -377,10 +396,10 @@ pub unsafe extern "C" fn call_contract(
         status,
         frame,
     });
-    assert_eq!(read_fixed(address_ptr), address);
-    assert_eq!(read_bytes(calldata, calldata_len), &*data);
-    assert_eq!(read_fixed(value_ptr), value.to_be_bytes::<32>());
-    assert_eq!(gas_supplied, gas);
+    check!(address, read_fixed(address_ptr), address);
+    check!(data, read_bytes(calldata, calldata_len), &*data);
+    check!(value, read_fixed(value_ptr), value.to_be_bytes::<32>());
+    check!(gas, gas_supplied, gas);
     *return_data_len = outs_len;
     status
 }
@@ -426,9 +445,9 @@ pub unsafe extern "C" fn delegate_call_contract(
         status,
         frame,
     });
-    assert_eq!(read_fixed(address_ptr), address);
-    assert_eq!(read_bytes(calldata, calldata_len), &*data);
-    assert_eq!(gas_supplied, gas);
+    check!(address, read_fixed(address_ptr), address);
+    check!(data, read_bytes(calldata, calldata_len), &*data);
+    check!(gas, gas_supplied, gas);
     *return_data_len = outs_len;
     status
 }
@@ -473,9 +492,9 @@ pub unsafe extern "C" fn static_call_contract(
         status,
         frame,
     });
-    assert_eq!(read_fixed(address_ptr), address);
-    assert_eq!(read_bytes(calldata, calldata_len), &*data);
-    assert_eq!(gas_supplied, gas);
+    check!(address, read_fixed(address_ptr), address);
+    check!(data, read_bytes(calldata, calldata_len), &*data);
+    check!(gas, gas_supplied, gas);
     *return_data_len = outs_len;
     status
 }
@@ -533,8 +552,8 @@ pub unsafe extern "C" fn create1(
         address,
         revert_data_len
     });
-    assert_eq!(read_bytes(code_ptr, code_len), &*code);
-    assert_eq!(read_fixed(value), endowment.to_be_bytes::<32>());
+    check!(code, read_bytes(code_ptr, code_len), &*code);
+    check!(endowment, read_fixed(value), endowment.to_be_bytes::<32>());
     copy!(address, contract);
     *revert_data_len_ptr = revert_data_len;
 }
@@ -580,9 +599,13 @@ pub unsafe extern "C" fn create2(
         address,
         revert_data_len
     });
-    assert_eq!(read_bytes(code_ptr, code_len), &*code);
-    assert_eq!(read_fixed(value_ptr), endowment.to_be_bytes::<32>());
-    assert_eq!(read_fixed(salt_ptr), salt);
+    check!(code, read_bytes(code_ptr, code_len), &*code);
+    check!(
+        endowment,
+        read_fixed(value_ptr),
+        endowment.to_be_bytes::<32>()
+    );
+    check!(salt, read_fixed(salt_ptr), salt);
     copy!(address, contract);
     *revert_data_len_ptr = revert_data_len;
 }
@@ -611,8 +634,8 @@ static CREATE2: unsafe extern "C" fn(
 #[no_mangle]
 pub unsafe extern "C" fn emit_log(data_ptr: *const u8, len: u32, topic_count: u32) {
     frame!(EmitLog { data, topics });
-    assert_eq!(read_bytes(data_ptr, len), &*data);
-    assert_eq!(topics, topic_count);
+    check!(data, read_bytes(data_ptr, len), &*data);
+    check!(topics, topic_count, topics);
 }
 
 #[used]
@@ -656,7 +679,7 @@ static EVM_INK_LEFT: unsafe extern "C" fn() -> u64 = evm_ink_left;
 #[no_mangle]
 pub unsafe extern "C" fn pay_for_memory_grow(new_pages: u16) {
     frame!(PayForMemoryGrow { pages });
-    assert_eq!(new_pages, pages);
+    check!(pages, new_pages, pages);
 }
 
 #[used]
@@ -669,15 +692,15 @@ static PAY_FOR_MEMORY_GROW: unsafe extern "C" fn(new_pages: u16) = pay_for_memor
 /// [`DIV`]: https://www.evm.codes/#04
 #[named]
 #[no_mangle]
-pub unsafe fn math_div(value: *mut u8, divisor: *const u8) {
+pub unsafe extern "C" fn math_div(value: *mut u8, divisor: *const u8) {
     frame!(MathDiv { a, b, result });
-    assert_eq!(read_fixed(value), a.to_be_bytes::<32>());
-    assert_eq!(read_fixed(divisor), b.to_be_bytes::<32>());
+    check!(a, read_fixed(value), a.to_be_bytes::<32>());
+    check!(b, read_fixed(divisor), b.to_be_bytes::<32>());
     copy!(result.to_be_bytes::<32>(), value);
 }
 
 #[used]
-static MATH_DIV: unsafe fn(value: *mut u8, divisor: *const u8) = math_div;
+static MATH_DIV: unsafe extern "C" fn(value: *mut u8, divisor: *const u8) = math_div;
 
 /// Computes `value % exponent` using 256-bit math, writing the result to the first.
 /// The semantics are equivalent to that of the EVM's [`MOD`] opcode, which means that a `modulus` of `0`
@@ -686,15 +709,15 @@ static MATH_DIV: unsafe fn(value: *mut u8, divisor: *const u8) = math_div;
 /// [`MOD`]: https://www.evm.codes/#06
 #[named]
 #[no_mangle]
-pub unsafe fn math_mod(value: *mut u8, modulus: *const u8) {
+pub unsafe extern "C" fn math_mod(value: *mut u8, modulus: *const u8) {
     frame!(MathMod { a, b, result });
-    assert_eq!(read_fixed(value), a.to_be_bytes::<32>());
-    assert_eq!(read_fixed(modulus), b.to_be_bytes::<32>());
+    check!(a, read_fixed(value), a.to_be_bytes::<32>());
+    check!(b, read_fixed(modulus), b.to_be_bytes::<32>());
     copy!(result.to_be_bytes::<32>(), value);
 }
 
 #[used]
-static MATH_MOD: unsafe fn(value: *mut u8, modulus: *const u8) = math_mod;
+static MATH_MOD: unsafe extern "C" fn(value: *mut u8, modulus: *const u8) = math_mod;
 
 /// Computes `value ^ exponent` using 256-bit math, writing the result to the first.
 /// The semantics are equivalent to that of the EVM's [`EXP`] opcode.
@@ -702,15 +725,15 @@ static MATH_MOD: unsafe fn(value: *mut u8, modulus: *const u8) = math_mod;
 /// [`EXP`]: https://www.evm.codes/#0A
 #[named]
 #[no_mangle]
-pub unsafe fn math_pow(value: *mut u8, exponent: *const u8) {
+pub unsafe extern "C" fn math_pow(value: *mut u8, exponent: *const u8) {
     frame!(MathPow { a, b, result });
-    assert_eq!(read_fixed(value), a.to_be_bytes::<32>());
-    assert_eq!(read_fixed(exponent), b.to_be_bytes::<32>());
+    check!(a, read_fixed(value), a.to_be_bytes::<32>());
+    check!(b, read_fixed(exponent), b.to_be_bytes::<32>());
     copy!(result.to_be_bytes::<32>(), value);
 }
 
 #[used]
-static MATH_POW: unsafe fn(value: *mut u8, exponent: *const u8) = math_pow;
+static MATH_POW: unsafe extern "C" fn(value: *mut u8, exponent: *const u8) = math_pow;
 
 /// Computes `(value + addend) % modulus` using 256-bit math, writing the result to the first.
 /// The semantics are equivalent to that of the EVM's [`ADDMOD`] opcode, which means that a `modulus` of `0`
@@ -719,16 +742,16 @@ static MATH_POW: unsafe fn(value: *mut u8, exponent: *const u8) = math_pow;
 /// [`ADDMOD`]: https://www.evm.codes/#08
 #[named]
 #[no_mangle]
-pub unsafe fn math_add_mod(value: *mut u8, addend: *const u8, modulus: *const u8) {
+pub unsafe extern "C" fn math_add_mod(value: *mut u8, addend: *const u8, modulus: *const u8) {
     frame!(MathAddMod { a, b, c, result });
-    assert_eq!(read_fixed(value), a.to_be_bytes::<32>());
-    assert_eq!(read_fixed(addend), b.to_be_bytes::<32>());
-    assert_eq!(read_fixed(modulus), c.to_be_bytes::<32>());
+    check!(a, read_fixed(value), a.to_be_bytes::<32>());
+    check!(b, read_fixed(addend), b.to_be_bytes::<32>());
+    check!(c, read_fixed(modulus), c.to_be_bytes::<32>());
     copy!(result.to_be_bytes::<32>(), value);
 }
 
 #[used]
-static MATH_ADD_MOD: unsafe fn(value: *mut u8, addend: *const u8, modulus: *const u8) =
+static MATH_ADD_MOD: unsafe extern "C" fn(value: *mut u8, addend: *const u8, modulus: *const u8) =
     math_add_mod;
 
 /// Computes `(value * multiplier) % modulus` using 256-bit math, writing the result to the first.
@@ -738,17 +761,20 @@ static MATH_ADD_MOD: unsafe fn(value: *mut u8, addend: *const u8, modulus: *cons
 /// [`MULMOD`]: https://www.evm.codes/#09
 #[named]
 #[no_mangle]
-pub unsafe fn math_mul_mod(value: *mut u8, multiplier: *const u8, modulus: *const u8) {
-    frame!(MathAddMod { a, b, c, result });
-    assert_eq!(read_fixed(value), a.to_be_bytes::<32>());
-    assert_eq!(read_fixed(multiplier), b.to_be_bytes::<32>());
-    assert_eq!(read_fixed(modulus), c.to_be_bytes::<32>());
+pub unsafe extern "C" fn math_mul_mod(value: *mut u8, multiplier: *const u8, modulus: *const u8) {
+    frame!(MathMulMod { a, b, c, result });
+    check!(a, read_fixed(value), a.to_be_bytes::<32>());
+    check!(b, read_fixed(multiplier), b.to_be_bytes::<32>());
+    check!(c, read_fixed(modulus), c.to_be_bytes::<32>());
     copy!(result.to_be_bytes::<32>(), value);
 }
 
 #[used]
-static MATH_MUL_MOD: unsafe fn(value: *mut u8, multiplier: *const u8, modulus: *const u8) =
-    math_mul_mod;
+static MATH_MUL_MOD: unsafe extern "C" fn(
+    value: *mut u8,
+    multiplier: *const u8,
+    modulus: *const u8,
+) = math_mul_mod;
 
 /// Whether the current call is reentrant.
 #[named]
@@ -804,7 +830,7 @@ static MSG_VALUE: unsafe extern "C" fn(dest: *mut u8) = msg_value;
 #[no_mangle]
 pub unsafe extern "C" fn native_keccak256(bytes: *const u8, len: u32, output: *mut u8) {
     frame!(NativeKeccak256 { preimage, digest });
-    assert_eq!(read_bytes(bytes, len), &*preimage);
+    check!(preimage, read_bytes(bytes, len), &*preimage);
     copy!(digest, output);
 }
 
@@ -825,8 +851,8 @@ pub unsafe extern "C" fn read_return_data(
     size_value: u32,
 ) -> u32 {
     frame!(ReadReturnData { offset, size, data });
-    assert_eq!(offset_value, offset);
-    assert_eq!(size_value, size);
+    check!(offset, offset_value, offset);
+    check!(size, size_value, size);
     copy!(data, dest, data.len());
     data.len() as u32
 }
@@ -900,7 +926,7 @@ static TX_ORIGIN: unsafe extern "C" fn(dest: *mut u8) = tx_origin;
 #[named]
 #[no_mangle]
 pub unsafe extern "C" fn log_f32(value: f32) {
-    frame!(ConsoleLog { text });
+    frame!("console_log", ConsoleLog { text });
     println!("{text}");
 }
 
@@ -912,7 +938,7 @@ static LOG_F32: unsafe extern "C" fn(value: f32) = log_f32;
 #[named]
 #[no_mangle]
 pub unsafe extern "C" fn log_f64(value: f64) {
-    frame!(ConsoleLog { text });
+    frame!("console_log", ConsoleLog { text });
     println!("{text}");
 }
 
@@ -924,7 +950,7 @@ static LOG_F64: unsafe extern "C" fn(value: f64) = log_f64;
 #[named]
 #[no_mangle]
 pub unsafe extern "C" fn log_i32(value: i32) {
-    frame!(ConsoleLog { text });
+    frame!("console_log", ConsoleLog { text });
     println!("{text}");
 }
 
@@ -936,7 +962,7 @@ static LOG_I32: unsafe extern "C" fn(value: i32) = log_i32;
 #[named]
 #[no_mangle]
 pub unsafe extern "C" fn log_i64(value: i64) {
-    frame!(ConsoleLog { text });
+    frame!("console_log", ConsoleLog { text });
     println!("{text}");
 }
 
@@ -947,8 +973,8 @@ static LOG_I64: unsafe extern "C" fn(value: i64) = log_i64;
 #[named]
 #[no_mangle]
 pub unsafe extern "C" fn log_txt(text_ptr: *const u8, len: u32) {
-    frame!(ConsoleLogText { text });
-    assert_eq!(read_bytes(text_ptr, len), &*text);
+    frame!("console_log_text", ConsoleLogText { text });
+    check!(text, read_bytes(text_ptr, len), &*text);
 }
 
 #[used]
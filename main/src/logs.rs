@@ -0,0 +1,293 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Queries the logs of a deployed contract and decodes them with the events of its ABI.
+
+use crate::{
+    export_abi::load_abi,
+    util::{
+        abi::{format_value, value_to_json},
+        color::{Color, DebugColor},
+    },
+    LogsArgs,
+};
+use alloy::{
+    dyn_abi::{DynSolValue, EventExt},
+    eips::BlockNumberOrTag,
+    hex,
+    json_abi::Event,
+    primitives::{LogData, B256},
+    providers::{Provider, ProviderBuilder},
+    rpc::types::{Filter, Log},
+};
+use eyre::{bail, eyre, Result};
+use serde_json::{json, Map, Value};
+use std::time::Duration;
+
+/// A log decoded as one of the ABI's events.
+struct DecodedLog<'a> {
+    event: &'a Event,
+    fields: Vec<(&'a str, DynSolValue)>,
+}
+
+pub async fn logs(args: &LogsArgs) -> Result<()> {
+    let abi = load_abi(args.abi.as_deref(), args.rust_features.clone())?;
+    let events: Vec<&Event> = match &args.event {
+        Some(name) => abi
+            .event(name)
+            .ok_or_else(|| eyre!("ABI has no event {name}"))?
+            .iter()
+            .collect(),
+        None => abi.events().collect(),
+    };
+
+    let provider = ProviderBuilder::new().connect(&args.endpoint).await?;
+    let mut filter = Filter::new().address(args.address);
+    if args.event.is_some() {
+        let selectors: Vec<B256> = events.iter().map(|x| x.selector()).collect();
+        filter = filter.event_signature(selectors);
+    }
+
+    // resolve the range up front so that polling resumes right after its end when following
+    let from = block_number(&provider, args.from_block).await?;
+    let mut head = block_number(&provider, args.to_block).await?;
+    let mut query = LogQuery {
+        provider: &provider,
+        filter,
+        events: &events,
+        json: args.json,
+        chunk: CHUNK_SIZE,
+    };
+    query.print(from, head).await?;
+    if !args.follow {
+        return Ok(());
+    }
+    loop {
+        tokio::time::sleep(Duration::from_secs(args.interval)).await;
+        let latest = provider.get_block_number().await?;
+        if latest <= head {
+            continue;
+        }
+        query.print(head + 1, latest).await?;
+        head = latest;
+    }
+}
+
+/// The most blocks queried at once, halved whenever the node rejects a range as too large.
+const CHUNK_SIZE: u64 = 10_000;
+
+async fn block_number(provider: &impl Provider, block: BlockNumberOrTag) -> Result<u64> {
+    match block {
+        BlockNumberOrTag::Number(number) => Ok(number),
+        BlockNumberOrTag::Earliest => Ok(0),
+        tag => match provider.get_block_by_number(tag).await? {
+            Some(block) => Ok(block.header.number),
+            None => bail!("node has no {tag} block"),
+        },
+    }
+}
+
+/// Queries and prints the logs matching a filter.
+struct LogQuery<'a, P> {
+    provider: &'a P,
+    filter: Filter,
+    events: &'a [&'a Event],
+    json: bool,
+    /// The number of blocks to query at once.
+    chunk: u64,
+}
+
+impl<P: Provider> LogQuery<'_, P> {
+    /// Prints the logs of an inclusive block range, querying it in chunks small enough for the
+    /// node.
+    async fn print(&mut self, from: u64, to: u64) -> Result<()> {
+        let mut start = from;
+        while start <= to {
+            let end = to.min(start.saturating_add(self.chunk - 1));
+            let range = self.filter.clone().from_block(start).to_block(end);
+            match self.provider.get_logs(&range).await {
+                Ok(logs) => {
+                    for log in logs {
+                        print_log(self.events, &log, self.json);
+                    }
+                    start = end + 1;
+                }
+                Err(err)
+                    if self.chunk > 1
+                        && err
+                            .as_error_resp()
+                            .is_some_and(|resp| range_too_large(&resp.message)) =>
+                {
+                    self.chunk /= 2;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Whether a node's error message means a log query spans too many blocks or results.
+fn range_too_large(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "block range",
+        "query returned more than",
+        "too many results",
+        "response size exceeded",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Decodes a log as the first of the events whose selector and fields match.
+fn decode<'a>(events: &[&'a Event], log: &LogData) -> Option<DecodedLog<'a>> {
+    let selector = *log.topics().first()?;
+    events
+        .iter()
+        .filter(|event| !event.anonymous && event.selector() == selector)
+        .find_map(|event| {
+            let decoded = event.decode_log(log).ok()?;
+            let mut indexed = decoded.indexed.into_iter();
+            let mut body = decoded.body.into_iter();
+            let fields = event
+                .inputs
+                .iter()
+                .map(|input| {
+                    let value = match input.indexed {
+                        true => indexed.next(),
+                        false => body.next(),
+                    };
+                    Some((input.name.as_str(), value?))
+                })
+                .collect::<Option<_>>()?;
+            Some(DecodedLog { event, fields })
+        })
+}
+
+fn print_log(events: &[&Event], log: &Log, json: bool) {
+    if json {
+        println!("{}", log_to_json(events, log));
+        return;
+    }
+    let block = log.block_number.unwrap_or_default();
+    let tx = log.transaction_hash.unwrap_or_default();
+    let data = log.data();
+    let description = match decode(events, data) {
+        Some(decoded) => {
+            let fields: Vec<_> = decoded
+                .fields
+                .iter()
+                .map(|(name, value)| match name.is_empty() {
+                    true => format_value(value),
+                    false => format!("{}: {}", name.lavender(), format_value(value)),
+                })
+                .collect();
+            format!("{}({})", decoded.event.name.mint(), fields.join(", "))
+        }
+        None => format!(
+            "{} topics {:?} data {}",
+            "unknown".red(),
+            data.topics(),
+            hex::encode_prefixed(&data.data)
+        ),
+    };
+    println!(
+        "{} {} {description}",
+        format!("block {block}").grey(),
+        tx.debug_lavender()
+    );
+}
+
+/// Describes a log as a JSON object, with its fields decoded if it matches one of the events.
+fn log_to_json(events: &[&Event], log: &Log) -> Value {
+    let data = log.data();
+    let mut entry = json!({
+        "block": log.block_number,
+        "tx": log.transaction_hash,
+        "logIndex": log.log_index,
+    });
+    match decode(events, data) {
+        Some(decoded) => {
+            let fields: Map<_, _> = decoded
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(index, (name, value))| {
+                    let name = match name.is_empty() {
+                        true => index.to_string(),
+                        false => name.to_string(),
+                    };
+                    (name, value_to_json(value))
+                })
+                .collect();
+            entry["event"] = json!(decoded.event.name);
+            entry["fields"] = Value::Object(fields);
+        }
+        None => {
+            entry["event"] = Value::Null;
+            entry["topics"] = json!(data.topics());
+            entry["data"] = json!(hex::encode_prefixed(&data.data));
+        }
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::{
+        json_abi::JsonAbi,
+        primitives::{address, Address, U256},
+    };
+
+    #[test]
+    fn decode_logs() {
+        let abi = JsonAbi::parse([
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+            "event Approval(address indexed owner, address indexed spender, uint256 value)",
+        ])
+        .unwrap();
+        let events: Vec<_> = abi.events().collect();
+
+        let from = address!("000000000000000000000000000000000000dEaD");
+        let topics = vec![
+            abi.events["Transfer"][0].selector(),
+            from.into_word(),
+            Address::ZERO.into_word(),
+        ];
+        let data = U256::from(1000).to_be_bytes_vec();
+        let log = Log {
+            inner: alloy::primitives::Log {
+                address: Address::ZERO,
+                data: LogData::new_unchecked(topics, data.into()),
+            },
+            block_number: Some(7),
+            log_index: Some(2),
+            ..Default::default()
+        };
+
+        let decoded = decode(&events, log.data()).expect("failed to decode");
+        assert_eq!(decoded.event.name, "Transfer");
+        assert_eq!(decoded.fields[0], ("from", DynSolValue::Address(from)));
+        assert_eq!(decoded.fields[2].0, "value");
+
+        let entry = log_to_json(&events, &log);
+        assert_eq!(entry["block"], 7);
+        assert_eq!(entry["event"], "Transfer");
+        assert_eq!(entry["fields"]["from"], from.to_string());
+        assert_eq!(entry["fields"]["value"], "1000");
+
+        let unknown = LogData::new_unchecked(vec![B256::ZERO], Default::default());
+        assert!(decode(&events, &unknown).is_none());
+    }
+
+    #[test]
+    fn detect_range_errors() {
+        assert!(range_too_large("exceed maximum block range: 10000"));
+        assert!(range_too_large("query returned more than 10000 results"));
+        assert!(range_too_large("Log response size exceeded."));
+        assert!(!range_too_large("invalid params"));
+        assert!(!range_too_large("rate limit exceeded"));
+    }
+}
@@ -5,20 +5,31 @@
 #![cfg_attr(feature = "nightly", feature(test))]
 
 use alloy::{
-    primitives::{utils::parse_ether, Address, Bytes, TxHash, B256, U256},
-    providers::ProviderBuilder,
+    eips::{BlockId, BlockNumberOrTag},
+    primitives::{
+        keccak256,
+        utils::{format_ether, parse_ether},
+        Address, Bytes, TxHash, B256, U256,
+    },
+    providers::{Provider, ProviderBuilder},
 };
-use clap::{ArgGroup, Args, CommandFactory, Parser, Subcommand};
+use clap::{ArgGroup, Args, CommandFactory, Parser, Subcommand, ValueEnum};
 use constants::DEFAULT_ENDPOINT;
 use deploy::STYLUS_DEPLOYER_ADDRESS;
 use eyre::{bail, eyre, Context, Result};
+use macros::*;
+use project::BuildConfig;
 use std::{
     fmt,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 use tokio::runtime::Builder;
-use trace::Trace;
-use util::{color::Color, sys};
+use trace::{Trace, TraceFrame};
+use util::{
+    color::{Color, DebugColor},
+    sys,
+};
 
 // Conditional import for Unix-specific `CommandExt`
 #[cfg(unix)]
@@ -28,23 +39,35 @@ use std::{env, os::unix::process::CommandExt};
 #[cfg(windows)]
 use std::env;
 
+mod abi_diff;
 mod activate;
+mod bindgen;
 mod cache;
+mod call;
 mod check;
 mod constants;
 mod deploy;
 mod docker;
 mod export_abi;
+mod gas_report;
 mod gen;
 mod get_initcode;
 mod hostio;
+mod logs;
 mod macros;
 mod new;
 mod project;
+mod selectors;
+mod storage;
+mod storage_layout;
+mod test_gen;
 mod trace;
+mod trace_diff;
+mod upgrade_check;
 mod util;
 mod verify;
 mod wallet;
+mod wasm_replay;
 
 #[derive(Parser, Debug)]
 #[command(name = "stylus")]
@@ -79,7 +102,7 @@ enum Apis {
         /// The output file (defaults to stdout).
         #[arg(long)]
         output: Option<PathBuf>,
-        /// Write a JSON ABI instead using solc. Requires solc.
+        /// Write a JSON ABI instead of a Solidity interface.
         #[arg(long)]
         json: bool,
         /// Rust crate's features list. Required to include feature specific abi.
@@ -113,7 +136,7 @@ enum Apis {
     /// Verify the deployment of a Stylus contract.
     #[command(visible_alias = "v")]
     Verify(VerifyConfig),
-    /// Generate c code bindings for a Stylus contract.
+    /// Generate c code bindings for a Stylus contract, from solc JSON output or a plain JSON ABI.
     Cgen { input: PathBuf, out_dir: PathBuf },
     /// Replay a transaction in gdb.
     #[command(visible_alias = "r")]
@@ -124,6 +147,28 @@ enum Apis {
     /// Simulate a transaction.
     #[command(visible_alias = "s")]
     Simulate(SimulateArgs),
+    /// Compare the hostios, storage writes and ink of two traces.
+    TraceDiff(TraceDiffArgs),
+    /// Simulate the calls of a scenario file and report their cost per function.
+    GasReport(GasReportArgs),
+    /// Read a contract's storage, decoded using its storage layout.
+    Storage(StorageArgs),
+    /// Call a function of a deployed contract without sending a transaction.
+    Call(CallArgs),
+    /// Send a transaction calling a function of a deployed contract.
+    Send(SendArgs),
+    /// Query the logs of a deployed contract, decoded using its ABI.
+    Logs(LogsArgs),
+    /// Compare the ABIs of two versions of a contract, failing on breaking changes.
+    AbiDiff(AbiDiffArgs),
+    /// Export the storage layout of the entrypoint in the JSON format of solc's `storageLayout`.
+    ExportStorageLayout(ExportStorageLayoutArgs),
+    /// Compare the storage layouts of two versions of a contract, failing on unsafe changes.
+    UpgradeCheck(UpgradeCheckArgs),
+    /// List the selectors and interface IDs of the contract, failing on selector collisions.
+    Selectors(SelectorsArgs),
+    /// Generate typed client bindings from the contract's ABI.
+    Bindgen(BindgenArgs),
 }
 
 #[derive(Args, Clone, Debug)]
@@ -268,6 +313,13 @@ struct DeployConfig {
     /// If not set, uses the default version of the local cargo stylus binary.
     #[arg(long)]
     cargo_stylus_version: Option<String>,
+    /// The container runtime for reproducible runs, detected if not set.
+    #[arg(long, value_enum)]
+    container_runtime: Option<docker::ContainerRuntime>,
+    /// The network the reproducible container joins, such as `host` to reach a node listening on
+    /// localhost, or `bridge` to isolate it from the host.
+    #[arg(long, default_value = "host")]
+    container_network: String,
     /// If set, do not activate the program after deploying it
     #[arg(long)]
     no_activate: bool,
@@ -285,6 +337,12 @@ struct DeployConfig {
         allow_hyphen_values = true,
     )]
     constructor_args: Vec<String>,
+    /// A JSON file with the constructor arguments, keyed by parameter name.
+    #[arg(long, conflicts_with_all = ["constructor_args", "constructor_calldata"])]
+    constructor_args_file: Option<PathBuf>,
+    /// The ABI-encoded constructor arguments, without a selector.
+    #[arg(long, value_name = "HEX", conflicts_with = "constructor_args")]
+    constructor_calldata: Option<Bytes>,
     /// The amount of Ether sent to the contract through the constructor.
     #[arg(long, value_parser = parse_ether, default_value = "0")]
     constructor_value: U256,
@@ -308,6 +366,13 @@ pub struct VerifyConfig {
     /// If not set, uses the default version of the local cargo stylus binary.
     #[arg(long)]
     cargo_stylus_version: Option<String>,
+    /// The container runtime for reproducible runs, detected if not set.
+    #[arg(long, value_enum)]
+    container_runtime: Option<docker::ContainerRuntime>,
+    /// The network the reproducible container joins, such as `host` to reach a node listening on
+    /// localhost, or `bridge` to isolate it from the host.
+    #[arg(long, default_value = "host")]
+    container_network: String,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -323,6 +388,15 @@ struct ReplayArgs {
     /// Which specific package to build during replay, if any.
     #[arg(long)]
     package: Option<String>,
+    /// Replay without a debugger, reporting divergences from the onchain trace as JSON and
+    /// exiting with a non-zero status if any were found. Differing arguments are collected, while
+    /// a differing hostio ends the replay.
+    #[arg(long)]
+    headless: bool,
+    /// Run the release WASM in an embedded wasmer runtime instead of a native build under a
+    /// debugger, printing the ink consumed by each hostio.
+    #[arg(long)]
+    wasm: bool,
     /// Whether this process is the child of another.
     #[arg(short, long, hide(true))]
     child: bool,
@@ -334,16 +408,248 @@ struct TraceArgs {
     #[arg(short, long, default_value = "http://localhost:8547")]
     endpoint: String,
     /// Tx to replay.
-    #[arg(short, long)]
-    tx: TxHash,
+    #[arg(short, long, required_unless_present = "from_file")]
+    tx: Option<TxHash>,
     /// Project path.
     #[arg(short, long, default_value = ".")]
     project: PathBuf,
-    /// If set, use the native tracer instead of the JavaScript one. Notice the native tracer might not be available in the node.
+    #[command(flatten)]
+    tracer: TracerOpts,
+    /// Save the transaction and its raw trace to the given file.
+    #[arg(long)]
+    save: Option<PathBuf>,
+    /// Load a trace previously written with --save instead of querying the RPC endpoint.
+    #[arg(long, conflicts_with = "tx")]
+    from_file: Option<PathBuf>,
+    /// Write a stylus-sdk TestVM unit test reproducing the trace to the given file.
+    #[arg(long)]
+    emit_test: Option<PathBuf>,
+    /// Replay, or emit a test for, the first call to this contract instead of the transaction's
+    /// target.
+    #[arg(long)]
+    address: Option<Address>,
+    /// Replay, or emit a test for, a nested call by its dot-separated path of call indices, e.g.
+    /// `1.0` for the first call made by the second call of the transaction.
+    #[arg(long, value_delimiter = '.', conflicts_with = "address")]
+    frame: Vec<usize>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TraceDiffArgs {
+    /// First tx hash or trace file written with `trace --save`.
+    a: String,
+    /// Second tx hash or trace file written with `trace --save`.
+    b: String,
+    /// RPC endpoint.
+    #[arg(short, long, default_value = "http://localhost:8547")]
+    endpoint: String,
+    #[command(flatten)]
+    tracer: TracerOpts,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct TracerOpts {
+    /// Require the node's native tracer rather than falling back to the JavaScript one when the
+    /// node doesn't support it.
     #[arg(short, long, default_value_t = false)]
     use_native_tracer: bool,
 }
 
+#[derive(Args, Clone, Debug)]
+pub struct GasReportArgs {
+    /// JSON scenario file, listing the calls to simulate as `{"to", "calls": [{"sig", "args",
+    /// "value", "from", "to"}]}`.
+    scenario: PathBuf,
+    /// RPC endpoint.
+    #[arg(short, long, default_value = "http://localhost:8547")]
+    endpoint: String,
+    /// Block to simulate against, as a number, hash or tag.
+    #[arg(long, default_value = "latest")]
+    block: BlockId,
+    #[command(flatten)]
+    tracer: TracerOpts,
+    /// Write the report as JSON to the given file, to compare against in later runs.
+    #[arg(long)]
+    save: Option<PathBuf>,
+    /// Show the change in average gas since a report previously written with --save.
+    #[arg(long)]
+    compare: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ContractCallOpts {
+    /// The contract to call.
+    address: Address,
+    /// The function to call, by name or by signature such as `transfer(address,uint256)`.
+    function: String,
+    /// Arguments to the function.
+    #[arg(allow_hyphen_values = true)]
+    args: Vec<String>,
+    /// JSON ABI or Solidity interface of the contract (defaults to exporting the ABI of the
+    /// project in the current directory).
+    #[arg(long)]
+    abi: Option<PathBuf>,
+    /// Rust crate's features list. Required to include feature specific abi.
+    #[arg(long)]
+    rust_features: Option<Vec<String>>,
+    /// Arbitrum RPC endpoint.
+    #[arg(short, long, default_value = DEFAULT_ENDPOINT)]
+    endpoint: String,
+    /// Value to send with the call, in Ether.
+    #[arg(long, value_parser = parse_ether, default_value = "0")]
+    value: U256,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct CallArgs {
+    #[command(flatten)]
+    target: ContractCallOpts,
+    /// Address to call from.
+    #[arg(long)]
+    from: Option<Address>,
+    /// Block to call at, as a number, hash or tag.
+    #[arg(long, default_value = "latest")]
+    block: BlockId,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct SendArgs {
+    #[command(flatten)]
+    target: ContractCallOpts,
+    /// Wallet source to use.
+    #[command(flatten)]
+    auth: AuthOpts,
+    /// Optional max fee per gas in gwei units.
+    #[arg(long)]
+    max_fee_per_gas_gwei: Option<String>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct AbiDiffArgs {
+    /// The old version, as an ABI file, a project directory or a git revision of the project in
+    /// the current directory. Deployed addresses aren't accepted, as a contract's ABI isn't stored
+    /// onchain.
+    old: String,
+    /// The new version (defaults to the project in the current directory).
+    #[arg(default_value = ".")]
+    new: String,
+    /// Rust crate's features list. Required to include feature specific abi.
+    #[arg(long)]
+    rust_features: Option<Vec<String>>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ExportStorageLayoutArgs {
+    /// The output file (defaults to stdout).
+    #[arg(long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct UpgradeCheckArgs {
+    /// The old version, as a layout file, a project directory or a git revision of the project in
+    /// the current directory.
+    old: String,
+    /// The new version (defaults to the project in the current directory).
+    #[arg(default_value = ".")]
+    new: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct SelectorsArgs {
+    /// Rust crate's features list. Required to include feature specific abi.
+    #[arg(long)]
+    rust_features: Option<Vec<String>>,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct BindgenArgs {
+    /// Language of the bindings.
+    #[arg(long, value_enum)]
+    lang: BindgenLang,
+    /// Directory to write the bindings to.
+    #[arg(long, default_value = "bindings")]
+    out_dir: PathBuf,
+    /// Name of the contract (defaults to the name of the project).
+    #[arg(long)]
+    name: Option<String>,
+    /// JSON file mapping chain IDs to the addresses the contract is deployed at, such as
+    /// `{"421614": "0x..."}`, to include in the TypeScript bindings. The file is maintained by
+    /// hand, as `deploy` doesn't record deployments.
+    #[arg(long)]
+    deployments: Option<PathBuf>,
+    /// JSON ABI or Solidity interface of the contract (defaults to exporting the ABI of the
+    /// project in the current directory).
+    #[arg(long)]
+    abi: Option<PathBuf>,
+    /// Rust crate's features list. Required to include feature specific abi.
+    #[arg(long)]
+    rust_features: Option<Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum BindgenLang {
+    /// A crate of `alloy::sol!` bindings.
+    Rust,
+    /// A TypeScript module with the ABI, function and event types, and deployment addresses.
+    Ts,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct LogsArgs {
+    /// The contract whose logs to query.
+    #[arg(long)]
+    address: Address,
+    /// First block to query, as a number or tag.
+    #[arg(long, default_value = "earliest", value_parser = parse_block_number)]
+    from_block: BlockNumberOrTag,
+    /// Last block to query, as a number or tag.
+    #[arg(long, default_value = "latest", value_parser = parse_block_number)]
+    to_block: BlockNumberOrTag,
+    /// Only query logs of the event with this name.
+    #[arg(long)]
+    event: Option<String>,
+    /// Print each log as a line of JSON.
+    #[arg(long)]
+    json: bool,
+    /// Keep polling for new logs after printing those already in the chain.
+    #[arg(long, conflicts_with = "to_block")]
+    follow: bool,
+    /// Seconds between polls when following new logs.
+    #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+    interval: u64,
+    /// JSON ABI or Solidity interface of the contract (defaults to exporting the ABI of the
+    /// project in the current directory).
+    #[arg(long)]
+    abi: Option<PathBuf>,
+    /// Rust crate's features list. Required to include feature specific abi.
+    #[arg(long)]
+    rust_features: Option<Vec<String>>,
+    /// Arbitrum RPC endpoint.
+    #[arg(short, long, default_value = DEFAULT_ENDPOINT)]
+    endpoint: String,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct StorageArgs {
+    /// The contract to read.
+    #[arg(long)]
+    address: Address,
+    /// Storage layout, in the JSON format of solc's `storageLayout` output (defaults to the layout
+    /// of the project in the current directory).
+    #[arg(long)]
+    layout: Option<PathBuf>,
+    /// Variables to read, such as `owner`, `balances[0xdead]` or `points[3].x` (defaults to every
+    /// variable in the layout).
+    paths: Vec<String>,
+    /// RPC endpoint.
+    #[arg(short, long, default_value = "http://localhost:8547")]
+    endpoint: String,
+    /// Block to read at, as a number, hash or tag.
+    #[arg(long, default_value = "latest")]
+    block: BlockId,
+}
+
 #[derive(Args, Clone, Debug)]
 pub struct SimulateArgs {
     /// RPC endpoint.
@@ -378,9 +684,49 @@ pub struct SimulateArgs {
     #[arg(short, long, default_value = ".")]
     project: PathBuf,
 
-    /// If set, use the native tracer instead of the JavaScript one.
-    #[arg(short, long, default_value_t = false)]
-    use_native_tracer: bool,
+    #[command(flatten)]
+    tracer: TracerOpts,
+
+    /// Block to simulate against, as a number, hash or tag.
+    #[arg(long, default_value = "latest")]
+    block: BlockId,
+
+    /// Override an account's balance, as `<address>=<wei>`.
+    #[arg(long = "balance", value_name = "ADDRESS=WEI", value_parser = parse_account_override::<U256>)]
+    balance_overrides: Vec<(Address, U256)>,
+
+    /// Override an account's code, as `<address>=<hex>`.
+    #[arg(long = "code", value_name = "ADDRESS=CODE", value_parser = parse_account_override::<Bytes>)]
+    code_overrides: Vec<(Address, Bytes)>,
+
+    /// Override an account's nonce, as `<address>=<nonce>`.
+    #[arg(long = "nonce", value_name = "ADDRESS=NONCE", value_parser = parse_account_override::<u64>)]
+    nonce_overrides: Vec<(Address, u64)>,
+
+    /// Override a storage slot, as `<address>:<slot>=<value>`.
+    #[arg(long = "storage", value_name = "ADDRESS:SLOT=VALUE", value_parser = parse_storage_override)]
+    storage_overrides: Vec<(Address, B256, B256)>,
+
+    /// Function signature to ABI-encode the calldata with, e.g. "transfer(address,uint256)".
+    #[arg(long, conflicts_with = "data")]
+    sig: Option<String>,
+
+    /// Arguments to the function given by --sig.
+    #[arg(requires = "sig")]
+    args: Vec<String>,
+
+    /// Build the project and call it without deploying, injecting its code with a state override.
+    /// The call is routed through an activator contract, which becomes the `msg.sender`.
+    #[arg(long, conflicts_with = "to")]
+    local: bool,
+
+    /// Address to inject the locally built contract at. Defaults to a random address.
+    #[arg(long, requires = "local")]
+    local_address: Option<Address>,
+
+    /// Any features that should be passed to cargo build.
+    #[arg(long, requires = "local")]
+    features: Option<Vec<String>>,
 }
 
 #[derive(Clone, Debug, Args)]
@@ -407,6 +753,43 @@ struct AuthOpts {
     keystore_password_path: Option<PathBuf>,
 }
 
+/// Parses an `<address>=<value>` account override.
+fn parse_account_override<T>(arg: &str) -> Result<(Address, T)>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let Some((address, value)) = arg.split_once('=') else {
+        bail!("expected <address>=<value>, got {arg}");
+    };
+    let value = value
+        .parse()
+        .map_err(|e| eyre!("invalid override value {value}: {e}"))?;
+    Ok((address.parse()?, value))
+}
+
+/// Parses a block number in decimal or hex, or a tag like `latest`.
+fn parse_block_number(arg: &str) -> Result<BlockNumberOrTag> {
+    match arg.parse()? {
+        BlockId::Number(number) => Ok(number),
+        BlockId::Hash(_) => bail!("expected a block number or tag, got hash {arg}"),
+    }
+}
+
+/// Parses an `<address>:<slot>=<value>` storage override, where the slot and value are words.
+fn parse_storage_override(arg: &str) -> Result<(Address, B256, B256)> {
+    let Some((address, slot, value)) = arg
+        .split_once(':')
+        .and_then(|(address, rest)| Some((address, rest.split_once('=')?)))
+        .map(|(address, (slot, value))| (address, slot, value))
+    else {
+        bail!("expected <address>:<slot>=<value>, got {arg}");
+    };
+    let slot: U256 = slot.parse()?;
+    let value: U256 = value.parse()?;
+    Ok((address.parse()?, slot.into(), value.into()))
+}
+
 pub trait GasFeeConfig {
     fn get_max_fee_per_gas_wei(&self) -> Result<Option<u128>>;
     fn get_fee_str(&self) -> &Option<String>;
@@ -461,22 +844,37 @@ impl GasFeeConfig for CacheBidConfig {
     }
 }
 
-impl fmt::Display for DataFeeOpts {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "--data-fee-bump-percent={}", self.data_fee_bump_percent)
+impl GasFeeConfig for SendArgs {
+    fn get_fee_str(&self) -> &Option<String> {
+        &self.max_fee_per_gas_gwei
+    }
+
+    fn get_max_fee_per_gas_wei(&self) -> Result<Option<u128>> {
+        match self.get_fee_str() {
+            Some(fee_str) => Ok(Some(convert_gwei_to_wei(fee_str)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl DataFeeOpts {
+    fn args(&self) -> Vec<String> {
+        vec![format!(
+            "--data-fee-bump-percent={}",
+            self.data_fee_bump_percent
+        )]
     }
 }
 
-impl fmt::Display for CommonConfig {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl CommonConfig {
+    fn args(&self) -> Vec<String> {
         let mut args = Vec::new();
         args.push(format!("--endpoint={}", self.endpoint));
         if self.verbose {
             args.push("--verbose".to_string());
         }
-        if !self.source_files_for_project_hash.is_empty() {
-            let files = self.source_files_for_project_hash.join(",");
-            args.push(format!("--source-files-for-project-hash={}", files));
+        for file in &self.source_files_for_project_hash {
+            args.push(format!("--source-files-for-project-hash={file}"));
         }
         if let Some(fee) = &self.max_fee_per_gas_gwei {
             args.push(format!("--max-fee-per-gas-gwei={fee}"));
@@ -484,30 +882,45 @@ impl fmt::Display for CommonConfig {
         if let Some(features) = &self.features {
             args.push(format!("--features={features}"));
         }
-        write!(f, "{}", args.join(" "))
+        args
+    }
+
+    /// Mounts the source files given by absolute paths or paths leaving the project into the
+    /// container, where only the project directory is mounted at the working directory. Patterns
+    /// within the project, and those not naming a file, are forwarded as is.
+    fn forward(&mut self, mounts: &mut docker::Mounts) -> Result<()> {
+        for file in &mut self.source_files_for_project_hash {
+            let path = Path::new(file.as_str());
+            let outside = path.is_absolute()
+                || path
+                    .components()
+                    .any(|x| matches!(x, std::path::Component::ParentDir));
+            if outside && path.is_file() {
+                *file = mounts.file(path)?.display().to_string();
+            }
+        }
+        Ok(())
     }
 }
 
-impl fmt::Display for CheckConfig {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut args = Vec::new();
-        args.push(format!("{}", self.common_cfg));
-        args.push(format!("{}", self.data_fee));
+impl CheckConfig {
+    fn args(&self) -> Vec<String> {
+        let mut args = self.common_cfg.args();
+        args.extend(self.data_fee.args());
         if let Some(wasm_file) = &self.wasm_file {
             args.push(format!("--wasm-file={}", wasm_file.display()));
         }
         if let Some(address) = &self.contract_address {
             args.push(format!("--contract-address={address:?}"));
         }
-        write!(f, "{}", args.join(" "))
+        args
     }
 }
 
-impl fmt::Display for DeployConfig {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut args = Vec::new();
-        args.push(format!("{}", self.check_config));
-        args.push(format!("{}", self.auth));
+impl DeployConfig {
+    fn args(&self) -> Vec<String> {
+        let mut args = self.check_config.args();
+        args.extend(self.auth.args());
         if self.estimate_gas {
             args.push("--estimate-gas".to_string());
         }
@@ -522,21 +935,52 @@ impl fmt::Display for DeployConfig {
         }
         args.push(format!("--deployer-address={:?}", self.deployer_address));
         args.push(format!("--deployer-salt={}", self.deployer_salt));
-        args.push(format!("--constructor-value={}", self.constructor_value));
+        args.push(format!(
+            "--constructor-value={}",
+            format_ether(self.constructor_value)
+        ));
         if let Some(signature) = &self.constructor_signature {
-            args.push(format!("--constructor-signature='{}'", signature));
+            args.push(format!("--constructor-signature={signature}"));
+        }
+        if let Some(path) = &self.constructor_args_file {
+            args.push(format!("--constructor-args-file={}", path.display()));
+        }
+        if let Some(calldata) = &self.constructor_calldata {
+            args.push(format!("--constructor-calldata={calldata}"));
         }
         // constructor args must be last
         if !self.constructor_args.is_empty() {
             args.push("--constructor-args".to_string());
             args.extend_from_slice(&self.constructor_args);
         }
-        write!(f, "{}", args.join(" "))
+        args
+    }
+
+    /// Whether any constructor arguments were given.
+    fn has_constructor_args(&self) -> bool {
+        !self.constructor_args.is_empty()
+            || self.constructor_args_file.is_some()
+            || self.constructor_calldata.is_some()
+    }
+
+    /// The arguments of the reproducible run, with the files the deploy reads mounted into the
+    /// container and the private key passed through a mounted file.
+    fn forward(&self, mounts: &mut docker::Mounts) -> Result<Vec<String>> {
+        let mut config = self.clone();
+        if let Some(path) = &config.check_config.wasm_file {
+            config.check_config.wasm_file = Some(mounts.file(path)?);
+        }
+        if let Some(path) = &config.constructor_args_file {
+            config.constructor_args_file = Some(mounts.file(path)?);
+        }
+        config.check_config.common_cfg.forward(mounts)?;
+        config.auth.forward(mounts)?;
+        Ok(config.args())
     }
 }
 
-impl fmt::Display for AuthOpts {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl AuthOpts {
+    fn args(&self) -> Vec<String> {
         let mut args = Vec::new();
         if let Some(path) = &self.private_key_path {
             args.push(format!("--private-key-path={}", path.display()));
@@ -550,14 +994,32 @@ impl fmt::Display for AuthOpts {
         if let Some(path) = &self.keystore_password_path {
             args.push(format!("--keystore-password-path={}", path.display()));
         }
-        write!(f, "{}", args.join(" "))
+        args
+    }
+
+    /// Mounts the key files into the container, replacing a raw private key with a mounted file
+    /// so that it never appears in the container's command line.
+    fn forward(&mut self, mounts: &mut docker::Mounts) -> Result<()> {
+        if let Some(path) = &self.private_key_path {
+            self.private_key_path = Some(mounts.file(path)?);
+        }
+        if let Some(key) = self.private_key.take() {
+            self.private_key_path = Some(mounts.secret("private-key", &key)?);
+        }
+        if let Some(path) = &self.keystore_path {
+            let path = mounts.file(Path::new(path))?;
+            self.keystore_path = Some(path.display().to_string());
+        }
+        if let Some(path) = &self.keystore_password_path {
+            self.keystore_password_path = Some(mounts.file(path)?);
+        }
+        Ok(())
     }
 }
 
-impl fmt::Display for VerifyConfig {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mut args = Vec::new();
-        args.push(format!("{}", self.common_cfg));
+impl VerifyConfig {
+    fn args(&self) -> Vec<String> {
+        let mut args = self.common_cfg.args();
         args.push(format!("--deployment-tx={}", self.deployment_tx));
         if self.no_verify {
             args.push("--no-verify".to_string());
@@ -565,10 +1027,38 @@ impl fmt::Display for VerifyConfig {
         if let Some(version) = &self.cargo_stylus_version {
             args.push(format!("--cargo-stylus-version={version}"));
         }
-        write!(f, "{}", args.join(" "))
+        args
+    }
+
+    /// The arguments of the reproducible run, with the source files it hashes mounted into the
+    /// container.
+    fn forward(&self, mounts: &mut docker::Mounts) -> Result<Vec<String>> {
+        let mut config = self.clone();
+        config.common_cfg.forward(mounts)?;
+        Ok(config.args())
     }
 }
 
+/// Displays configs as the command-line arguments that produce them.
+macro_rules! display_args {
+    ($($ty:ty),+) => {$(
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.args().join(" "))
+            }
+        }
+    )+};
+}
+
+display_args!(
+    DataFeeOpts,
+    CommonConfig,
+    CheckConfig,
+    DeployConfig,
+    AuthOpts,
+    VerifyConfig
+);
+
 // prints help message and exits
 fn exit_with_help_msg() -> ! {
     Opts::command().print_help().unwrap();
@@ -679,6 +1169,23 @@ async fn main_impl(args: Opts) -> Result<()> {
         }
         Apis::Trace(args) => run!(trace(args).await, "failed to trace tx"),
         Apis::Replay(args) => run!(replay(args).await, "failed to replay tx"),
+        Apis::GasReport(args) => run!(gas_report::gas_report(&args).await, "failed to report gas"),
+        Apis::TraceDiff(args) => run!(trace_diff::trace_diff(&args).await, "failed to diff traces"),
+        Apis::Storage(args) => run!(storage::storage(&args).await, "failed to read storage"),
+        Apis::Call(args) => run!(call::call(&args).await, "failed to call contract"),
+        Apis::Send(args) => run!(call::send(&args).await, "failed to send tx"),
+        Apis::Logs(args) => run!(logs::logs(&args).await, "failed to query logs"),
+        Apis::AbiDiff(args) => run!(abi_diff::abi_diff(&args), "failed to diff ABIs"),
+        Apis::ExportStorageLayout(args) => run!(
+            storage_layout::export_storage_layout(&args),
+            "failed to export storage layout"
+        ),
+        Apis::UpgradeCheck(args) => run!(
+            upgrade_check::upgrade_check(&args),
+            "failed to check upgrade"
+        ),
+        Apis::Selectors(args) => run!(selectors::selectors(&args), "failed to list selectors"),
+        Apis::Bindgen(args) => run!(bindgen::bindgen(&args), "failed to generate bindings"),
         Apis::Cache(subcommand) => match subcommand {
             Cache::Bid(config) => {
                 run!(
@@ -713,17 +1220,18 @@ async fn main_impl(args: Opts) -> Result<()> {
                     "Running in a Docker container for reproducibility, this may take a while",
                 );
                 println!("NOTE: You can opt out by doing --no-verify");
+                let mut mounts = docker::Mounts::default();
                 let mut commands: Vec<String> =
                     vec![String::from("deploy"), String::from("--no-verify")];
-                let config_args = config
-                    .to_string()
-                    .split(' ')
-                    .map(|s| s.to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect::<Vec<String>>();
-                commands.extend(config_args);
+                commands.extend(run!(config.forward(&mut mounts), "invalid deploy args"));
                 run!(
-                    docker::run_reproducible(config.cargo_stylus_version, &commands),
+                    docker::run_reproducible(
+                        config.container_runtime,
+                        config.cargo_stylus_version,
+                        &config.container_network,
+                        &commands,
+                        &mounts
+                    ),
                     "failed reproducible run"
                 );
             }
@@ -735,17 +1243,18 @@ async fn main_impl(args: Opts) -> Result<()> {
                 println!(
                     "Running in a Docker container for reproducibility, this may take a while",
                 );
+                let mut mounts = docker::Mounts::default();
                 let mut commands: Vec<String> =
                     vec![String::from("verify"), String::from("--no-verify")];
-                let config_args = config
-                    .to_string()
-                    .split(' ')
-                    .map(|s| s.to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect::<Vec<String>>();
-                commands.extend(config_args);
+                commands.extend(run!(config.forward(&mut mounts), "invalid verify args"));
                 run!(
-                    docker::run_reproducible(config.cargo_stylus_version, &commands),
+                    docker::run_reproducible(
+                        config.container_runtime,
+                        config.cargo_stylus_version,
+                        &config.container_network,
+                        &commands,
+                        &mounts
+                    ),
                     "failed reproducible run"
                 );
             }
@@ -755,22 +1264,90 @@ async fn main_impl(args: Opts) -> Result<()> {
 }
 
 async fn trace(args: TraceArgs) -> Result<()> {
-    let provider = ProviderBuilder::new().connect(&args.endpoint).await?;
-    let trace = Trace::new(&provider, args.tx, args.use_native_tracer).await?;
+    let trace = load_trace(&args).await?;
+    if let Some(path) = &args.emit_test {
+        let (frame, calldata) = select_frame(&trace, &args)?;
+        let entrypoint = storage_layout::entrypoint_name(&args.project)?;
+        test_gen::emit_test(frame, calldata, &entrypoint, path)?;
+        return Ok(());
+    }
     println!("{}", trace.json);
     Ok(())
 }
 
-async fn simulate(args: SimulateArgs) -> Result<()> {
+/// Selects the call chosen by `--address` or `--frame`, returning it with its calldata.
+fn select_frame<'a>(trace: &'a Trace, args: &TraceArgs) -> Result<(&'a TraceFrame, &'a [u8])> {
+    let frame = match args.address {
+        Some(address) => trace
+            .top_frame
+            .find_address(address)
+            .ok_or_else(|| eyre!("no call to {address} found in trace"))?,
+        None => trace.top_frame.find_path(&args.frame)?,
+    };
+    let calldata = if std::ptr::eq(frame, &trace.top_frame) {
+        trace.tx.input.input().map(|input| &input[..])
+    } else {
+        frame.calldata()
+    };
+    let Some(calldata) = calldata else {
+        bail!("missing calldata for the selected call, is it a Stylus contract?");
+    };
+    Ok((frame, calldata))
+}
+
+/// Fetches the trace from the node or from a saved file, saving it if requested.
+async fn load_trace(args: &TraceArgs) -> Result<Trace> {
+    let trace = match (&args.from_file, args.tx) {
+        (Some(path), _) => Trace::from_file(path)?,
+        (None, Some(tx)) => {
+            let provider = ProviderBuilder::new().connect(&args.endpoint).await?;
+            Trace::new(&provider, tx, args.tracer.use_native_tracer).await?
+        }
+        (None, None) => bail!("either --tx or --from-file must be provided"),
+    };
+    if let Some(path) = &args.save {
+        trace.save(path)?;
+    }
+    Ok(trace)
+}
+
+async fn simulate(mut args: SimulateArgs) -> Result<()> {
     let provider = ProviderBuilder::new().connect(&args.endpoint).await?;
+    if args.local {
+        let address = inject_local_contract(&mut args, &provider).await?;
+        egreyln!("simulating local contract at {}", address.debug_lavender());
+    }
     let trace = Trace::simulate(&provider, &args).await?;
     println!("{}", trace.json);
     Ok(())
 }
 
+/// Builds the project and overrides the state so that the simulated call activates and then
+/// calls it, returning the address of the contract.
+async fn inject_local_contract(
+    args: &mut SimulateArgs,
+    provider: &impl Provider,
+) -> Result<Address> {
+    env::set_current_dir(&args.project)?;
+    let features = args.features.as_ref().map(|f| f.join(","));
+    let (wasm, project_hash) = project::build_wasm_from_features(features, vec![])?;
+    let (_, code) = project::compress_wasm(&wasm, project_hash)?;
+
+    let address = args.local_address.unwrap_or_else(Address::random);
+    let activate = !check::contract_exists(keccak256(&code), provider).await?;
+    let activator = Address::random();
+    let activator_code = deploy::activate_and_call_code(address, activate);
+
+    args.to = Some(activator);
+    args.code_overrides.push((address, code.into()));
+    args.code_overrides.push((activator, activator_code.into()));
+    args.balance_overrides.push((activator, parse_ether("1")?));
+    Ok(address)
+}
+
 async fn replay(args: ReplayArgs) -> Result<()> {
     let macos = cfg!(target_os = "macos");
-    if !args.child {
+    if !args.child && !args.headless && !args.wasm {
         let gdb_args = [
             "--quiet",
             "-ex=set breakpoint pending on",
@@ -820,31 +1397,58 @@ async fn replay(args: ReplayArgs) -> Result<()> {
         bail!("failed to exec {cmd_name} {:?}", err);
     }
 
-    let provider = ProviderBuilder::new().connect(&args.trace.endpoint).await?;
-    let trace = Trace::new(&provider, args.trace.tx, args.trace.use_native_tracer).await?;
+    let trace = load_trace(&args.trace).await?;
 
-    build_shared_library(&args.trace.project, args.package, args.features)?;
-    let library_extension = if macos { ".dylib" } else { ".so" };
-    let shared_library = find_shared_library(&args.trace.project, library_extension)?;
+    let (frame, calldata) = select_frame(&trace, &args.trace)?;
+    let args_len = calldata.len();
+    let frame = frame.clone();
 
-    // TODO: don't assume the contract is top-level
-    let Some(args) = trace.tx.input.input() else {
-        bail!("missing transaction input");
-    };
-    let args_len = args.len();
-
-    unsafe {
-        *hostio::FRAME.lock() = Some(trace.reader());
+    let (status, divergences) = if args.wasm {
+        let mut cfg = BuildConfig::new(args.stable_rust);
+        cfg.features = args.features.map(|f| f.join(","));
+        cfg.package = args.package;
+        env::set_current_dir(&args.trace.project)?;
+        let wasm = project::build_dylib(cfg)?;
 
-        type Entrypoint = unsafe extern "C" fn(usize) -> usize;
-        let lib = libloading::Library::new(shared_library)?;
-        let main: libloading::Symbol<Entrypoint> = lib.get(b"user_entrypoint")?;
+        let replay = wasm_replay::replay(&wasm, frame, args_len.try_into()?, args.headless)?;
+        if !args.headless {
+            replay.print_ink();
+        }
+        let Some(status) = replay.status else {
+            if args.headless {
+                trace::print_report(None, &replay.divergences);
+            }
+            bail!("replay diverged from the onchain trace");
+        };
+        (status as usize, replay.divergences)
+    } else {
+        build_shared_library(&args.trace.project, args.package, args.features)?;
+        let library_extension = if macos { ".dylib" } else { ".so" };
+        let shared_library = find_shared_library(&args.trace.project, library_extension)?;
+
+        let status = unsafe {
+            *hostio::FRAME.lock() = Some(frame.reader(args.headless));
+
+            type Entrypoint = unsafe extern "C" fn(usize) -> usize;
+            let lib = libloading::Library::new(shared_library)?;
+            let main: libloading::Symbol<Entrypoint> = lib.get(b"user_entrypoint")?;
+            main(args_len)
+        };
+        let reader = hostio::FRAME.lock().take().unwrap();
+        (status, reader.finish())
+    };
 
-        match main(args_len) {
-            0 => println!("call completed successfully"),
-            1 => println!("call reverted"),
-            x => println!("call exited with unknown status code: {}", x.red()),
+    if args.headless {
+        trace::print_report(Some(status), &divergences);
+        if !divergences.is_empty() {
+            bail!("replay diverged from the onchain trace");
         }
+        return Ok(());
+    }
+    match status {
+        0 => println!("call completed successfully"),
+        1 => println!("call reverted"),
+        x => println!("call exited with unknown status code: {}", x.red()),
     }
     Ok(())
 }
@@ -932,7 +1536,8 @@ mod tests {
         let expected = concat!(
             "--endpoint=http://localhost:8545 ",
             "--verbose ",
-            "--source-files-for-project-hash=src/main.rs,Cargo.toml ",
+            "--source-files-for-project-hash=src/main.rs ",
+            "--source-files-for-project-hash=Cargo.toml ",
             "--max-fee-per-gas-gwei=150 ",
             "--features=full"
         );
@@ -996,6 +1601,8 @@ mod tests {
             estimate_gas: true,
             no_verify: true,
             cargo_stylus_version: Some("1.2.3".to_string()),
+            container_runtime: None,
+            container_network: "host".to_string(),
             no_activate: true,
             deployer_address: alloy::primitives::address!(
                 "fafafafafafafafafafafafafafafafafafafafa"
@@ -1004,6 +1611,8 @@ mod tests {
                 "bebebebebebebebebebebebebebebebebebebebebebebebebebebebebebebebe"
             ),
             constructor_args: vec!["arg1".to_string(), "123".to_string()],
+            constructor_args_file: None,
+            constructor_calldata: None,
             constructor_value: U256::from(1000),
             constructor_signature: Some("initialize(uint256)".to_string()),
         };
@@ -1019,8 +1628,8 @@ mod tests {
             "--no-activate ",
             "--deployer-address=0xfafafafafafafafafafafafafafafafafafafafa ",
             "--deployer-salt=0xbebebebebebebebebebebebebebebebebebebebebebebebebebebebebebebebe ",
-            "--constructor-value=1000 ",
-            "--constructor-signature='initialize(uint256)' ",
+            "--constructor-value=0.000000000000001000 ",
+            "--constructor-signature=initialize(uint256) ",
             "--constructor-args arg1 123"
         );
         assert_eq!(format!("{}", config), expected);
@@ -1056,6 +1665,8 @@ mod tests {
             deployment_tx: "0x123abc".to_string(),
             no_verify: true,
             cargo_stylus_version: Some("1.0.0".to_string()),
+            container_runtime: None,
+            container_network: "host".to_string(),
         };
         let expected = concat!(
             "--endpoint=http://verify.net ",
@@ -1069,4 +1680,116 @@ mod tests {
         );
         assert_eq!(format!("{}", config), expected);
     }
+
+    #[test]
+    fn parse_simulate_overrides() {
+        let address = "0x000000000000000000000000000000000000dEaD";
+        let (parsed, balance) = parse_account_override::<U256>(&format!("{address}=100")).unwrap();
+        assert_eq!(parsed, address.parse::<Address>().unwrap());
+        assert_eq!(balance, U256::from(100));
+        assert!(parse_account_override::<u64>(address).is_err());
+
+        let (_, slot, value) = parse_storage_override(&format!("{address}:0x1=42")).unwrap();
+        assert_eq!(slot, B256::from(U256::from(1)));
+        assert_eq!(value, B256::from(U256::from(42)));
+        assert!(parse_storage_override(&format!("{address}=42")).is_err());
+    }
+
+    #[test]
+    fn forward_deploy_args() {
+        let opts = Opts::try_parse_from([
+            "cargo-stylus",
+            "deploy",
+            "--private-key=0xabc",
+            "--constructor-calldata=0x01",
+            "--constructor-args",
+            "hello world",
+            "-1",
+        ]);
+        assert!(opts.is_err(), "calldata conflicts with positional args");
+
+        let opts = Opts::try_parse_from([
+            "cargo-stylus",
+            "deploy",
+            "--private-key=0xabc",
+            "--container-runtime=podman",
+            "--container-network=bridge",
+            "--constructor-args",
+            "hello world",
+            "-1",
+        ])
+        .unwrap();
+        let Apis::Deploy(config) = opts.command else {
+            panic!("expected a deploy command");
+        };
+        assert_eq!(
+            config.container_runtime,
+            Some(docker::ContainerRuntime::Podman)
+        );
+        assert_eq!(config.container_network, "bridge");
+        let mut mounts = docker::Mounts::default();
+        let args = config.forward(&mut mounts).unwrap();
+        assert_eq!(
+            args[args.len() - 3..],
+            ["--constructor-args", "hello world", "-1"]
+        );
+        assert!(args
+            .iter()
+            .all(|x| !x.contains("0xabc") && !x.contains("network")));
+        assert!(args.contains(&"--private-key-path=/mnt/stylus/0-private-key".to_string()));
+    }
+
+    #[test]
+    fn forward_round_trip() {
+        let key = tempfile::NamedTempFile::new().unwrap();
+        let key = format!("--private-key-path={}", key.path().display());
+        let outside = tempfile::NamedTempFile::new().unwrap();
+        let outside = outside.path().display().to_string();
+        let opts = Opts::try_parse_from([
+            "cargo-stylus",
+            "deploy",
+            &key,
+            "--constructor-value=1",
+            "--source-files-for-project-hash=src/*.rs",
+            "--source-files-for-project-hash",
+            &outside,
+        ])
+        .unwrap();
+        let Apis::Deploy(config) = opts.command else {
+            panic!("expected a deploy command");
+        };
+        let mut mounts = docker::Mounts::default();
+        let args = config.forward(&mut mounts).unwrap();
+
+        let forwarded = ["cargo-stylus", "deploy"].into_iter().map(String::from);
+        let opts = Opts::try_parse_from(forwarded.chain(args.clone())).unwrap();
+        let Apis::Deploy(forwarded) = opts.command else {
+            panic!("expected a deploy command");
+        };
+        assert_eq!(forwarded.args(), args);
+        assert_eq!(forwarded.constructor_value, config.constructor_value);
+        let files = &forwarded
+            .check_config
+            .common_cfg
+            .source_files_for_project_hash;
+        assert_eq!(files[0], "src/*.rs");
+        assert!(files[1].starts_with("/mnt/stylus/"));
+    }
+
+    #[test]
+    fn parse_block_numbers() {
+        assert_eq!(
+            parse_block_number("100").unwrap(),
+            BlockNumberOrTag::Number(100)
+        );
+        assert_eq!(
+            parse_block_number("0x10").unwrap(),
+            BlockNumberOrTag::Number(16)
+        );
+        assert_eq!(
+            parse_block_number("latest").unwrap(),
+            BlockNumberOrTag::Latest
+        );
+        assert!(parse_block_number(&B256::ZERO.to_string()).is_err());
+    }
 }
@@ -39,6 +39,8 @@ pub struct BuildConfig {
     pub opt_level: OptLevel,
     pub stable: bool,
     pub features: Option<String>,
+    /// The package to build within a workspace.
+    pub package: Option<String>,
 }
 
 impl BuildConfig {
@@ -63,7 +65,10 @@ pub fn build_dylib(cfg: BuildConfig) -> Result<PathBuf> {
     let mut cmd = sys::new_command("cargo");
 
     // Enforce a version is included in the Cargo.toml file.
-    let cargo_toml_path = cwd.join(Path::new("Cargo.toml"));
+    let cargo_toml_path = match &cfg.package {
+        Some(package) => package_manifest(package)?,
+        None => cwd.join(Path::new("Cargo.toml")),
+    };
     let cargo_toml_version = extract_cargo_toml_version(&cargo_toml_path)?;
     greyln!("Building project with Cargo.toml version: {cargo_toml_version}");
 
@@ -78,6 +83,9 @@ pub fn build_dylib(cfg: BuildConfig) -> Result<PathBuf> {
     if let Some(features) = cfg.features {
         cmd.arg(format!("--features={}", features.clone()));
     }
+    if let Some(package) = cfg.package {
+        cmd.arg(format!("--package={package}"));
+    }
 
     if !cfg.stable {
         cmd.arg("-Z");
@@ -144,6 +152,32 @@ pub fn build_dylib(cfg: BuildConfig) -> Result<PathBuf> {
     Ok(wasm_file_path)
 }
 
+/// Finds the manifest of a package in the workspace of the current directory.
+fn package_manifest(package: &str) -> Result<PathBuf> {
+    let output = sys::new_command("cargo")
+        .arg("metadata")
+        .arg("--no-deps")
+        .arg("--format-version=1")
+        .output()
+        .wrap_err("failed to execute cargo metadata")?;
+    if !output.status.success() {
+        bail!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let packages = metadata["packages"].as_array().cloned().unwrap_or_default();
+    let Some(manifest) = packages
+        .iter()
+        .find(|x| x["name"] == package)
+        .and_then(|x| x["manifest_path"].as_str())
+    else {
+        bail!("package {package} not found in the workspace");
+    };
+    Ok(manifest.into())
+}
+
 fn all_paths(root_dir: &Path, source_file_patterns: Vec<String>) -> Result<Vec<PathBuf>> {
     let mut files = Vec::<PathBuf>::new();
     let mut directories = Vec::<PathBuf>::new();
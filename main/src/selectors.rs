@@ -0,0 +1,148 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Lists the selectors of a contract and the ERC-165 IDs of its interfaces, checking that the
+//! functions of the contract and its inherited parents don't collide.
+
+use crate::{
+    export_abi::{get_interfaces, Interface},
+    macros::*,
+    util::color::{Color, DebugColor},
+    SelectorsArgs,
+};
+use alloy::primitives::Selector;
+use eyre::{bail, Result};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub fn selectors(args: &SelectorsArgs) -> Result<()> {
+    let interfaces = get_interfaces(args.rust_features.clone())?;
+
+    for interface in &interfaces {
+        let mut header = format!("interface {}", interface.name);
+        if !interface.parents.is_empty() {
+            header += &format!(" is {}", interface.parents.join(", "));
+        }
+        println!(
+            "{} {}",
+            header.lavender(),
+            format!("(interface ID {})", interface_id(interface)).grey()
+        );
+        for function in interface.abi.functions() {
+            println!(
+                "  {} function {}",
+                function.selector().debug_mint(),
+                function.signature()
+            );
+        }
+        for event in interface.abi.events().filter(|x| !x.anonymous) {
+            println!(
+                "  {} event {}",
+                event.selector().debug_mint(),
+                event.signature()
+            );
+        }
+        for error in interface.abi.errors() {
+            println!(
+                "  {} error {}",
+                error.selector().debug_mint(),
+                error.signature()
+            );
+        }
+    }
+
+    let collisions = collisions(&interfaces);
+    for (selector, signatures) in &collisions {
+        let signatures: Vec<_> = signatures.iter().map(String::as_str).collect();
+        println!(
+            "{} {} is shared by {}",
+            "collision:".red(),
+            selector.debug_lavender(),
+            signatures.join(", ")
+        );
+    }
+    if !collisions.is_empty() {
+        bail!("found {} selector collisions", collisions.len());
+    }
+    greyln!("no selector collisions");
+    Ok(())
+}
+
+/// The ERC-165 ID of an interface, which is the XOR of the selectors of the functions it declares,
+/// excluding inherited ones.
+fn interface_id(interface: &Interface) -> Selector {
+    interface
+        .abi
+        .functions()
+        .fold(Selector::ZERO, |id, x| id ^ x.selector())
+}
+
+/// Finds selectors shared by functions with different signatures, which the router of the
+/// contract can't tell apart.
+fn collisions(interfaces: &[Interface]) -> BTreeMap<Selector, BTreeSet<String>> {
+    let mut signatures: BTreeMap<Selector, BTreeSet<String>> = BTreeMap::new();
+    for interface in interfaces {
+        for function in interface.abi.functions() {
+            let signature = format!("{}.{}", interface.name, function.signature());
+            signatures
+                .entry(function.selector())
+                .or_default()
+                .insert(signature);
+        }
+    }
+    signatures.retain(|_, names| {
+        // a function overriding one of a parent's shares its signature
+        let distinct: BTreeSet<_> = names
+            .iter()
+            .map(|x| x.split_once('.').map_or(x.as_str(), |(_, x)| x))
+            .collect();
+        distinct.len() > 1
+    });
+    signatures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export_abi::parse_interfaces;
+
+    #[test]
+    fn find_collisions() {
+        let source = "
+interface IErc20 {
+    function transfer(address to, uint256 value) external returns (bool);
+
+    function burn(uint256 value) external;
+}
+
+interface IToken is IErc20 {
+    function transfer(address to, uint256 value) external returns (bool);
+
+    function collate_propagate_storage(bytes16) external;
+
+    event Paused(address account);
+}";
+        let interfaces = parse_interfaces(source).unwrap();
+        assert_eq!(interfaces.len(), 2);
+        assert_eq!(interfaces[1].name, "IToken");
+        assert_eq!(interfaces[1].parents, ["IErc20"]);
+
+        // the ERC-20 `transfer` selector XORed with the colliding `burn` selector
+        let erc20 =
+            Selector::from([0xa9, 0x05, 0x9c, 0xbb]) ^ Selector::from([0x42, 0x96, 0x6c, 0x68]);
+        assert_eq!(interface_id(&interfaces[0]), erc20);
+
+        let collisions = collisions(&interfaces);
+        assert_eq!(collisions.len(), 1);
+        let signatures: Vec<_> = collisions[&Selector::from([0x42, 0x96, 0x6c, 0x68])]
+            .iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(
+            signatures,
+            [
+                "IErc20.burn(uint256)",
+                "IToken.collate_propagate_storage(bytes16)"
+            ]
+        );
+    }
+}
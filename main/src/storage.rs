@@ -0,0 +1,604 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Reads a contract's storage and decodes it using a solc-style storage layout.
+
+use crate::{
+    storage_layout::project_layout,
+    util::{abi::format_value, color::Color},
+    StorageArgs,
+};
+use alloy::{
+    dyn_abi::DynSolType,
+    eips::BlockId,
+    hex,
+    primitives::{keccak256, Address, B256, U256},
+    providers::{Provider, ProviderBuilder},
+};
+use eyre::{bail, eyre, OptionExt, Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fs,
+    future::Future,
+    path::Path,
+    pin::Pin,
+};
+
+/// The most elements of an array to print when no index is given.
+const MAX_ELEMENTS: usize = 16;
+
+/// The most slots of a `bytes` or `string` value to read.
+const MAX_BYTES_SLOTS: usize = 1024;
+
+/// The `storageLayout` output of solc.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct StorageLayout {
+    pub storage: Vec<StorageEntry>,
+    #[serde(default)]
+    pub types: BTreeMap<String, StorageType>,
+}
+
+/// A state variable, or a member of a struct.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StorageEntry {
+    pub label: String,
+    /// The slot, as a decimal string.
+    pub slot: String,
+    /// The offset in bytes from the end of the slot.
+    pub offset: usize,
+    #[serde(rename = "type")]
+    pub ty: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageType {
+    /// One of `inplace`, `mapping`, `dynamic_array` or `bytes`.
+    pub encoding: String,
+    pub label: String,
+    pub number_of_bytes: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub members: Option<Vec<StorageEntry>>,
+}
+
+/// Where a value lives in storage.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Location {
+    pub slot: U256,
+    /// The offset in bytes from the end of the slot.
+    pub offset: usize,
+}
+
+/// A value read from storage.
+#[derive(Debug, PartialEq)]
+pub enum Decoded {
+    Value(String),
+    /// A mapping, whose entries can only be read given a key.
+    Mapping(String),
+    /// An array, of which only the first [`MAX_ELEMENTS`] are read.
+    Array {
+        len: usize,
+        items: Vec<Decoded>,
+    },
+    Struct(Vec<(String, Decoded)>),
+}
+
+/// A step of a path like `balances[0xdead].amount`.
+#[derive(Debug, PartialEq)]
+enum Accessor<'a> {
+    Index(&'a str),
+    Member(&'a str),
+}
+
+/// A source of raw storage slots.
+trait SlotReader {
+    async fn read(&self, slot: U256) -> Result<B256>;
+}
+
+/// Reads slots from a contract over RPC, reading each at most once.
+struct ChainStorage<P> {
+    provider: P,
+    address: Address,
+    block: BlockId,
+    cache: RefCell<HashMap<U256, B256>>,
+}
+
+impl<P: Provider> SlotReader for ChainStorage<P> {
+    async fn read(&self, slot: U256) -> Result<B256> {
+        if let Some(word) = self.cache.borrow().get(&slot) {
+            return Ok(*word);
+        }
+        let word = self
+            .provider
+            .get_storage_at(self.address, slot)
+            .block_id(self.block)
+            .await?;
+        let word = B256::from(word);
+        self.cache.borrow_mut().insert(slot, word);
+        Ok(word)
+    }
+}
+
+pub async fn storage(args: &StorageArgs) -> Result<()> {
+    let layout = match &args.layout {
+        Some(path) => StorageLayout::load(path)?,
+        None => project_layout(Path::new("."))?,
+    };
+    let provider = ProviderBuilder::new().connect(&args.endpoint).await?;
+    let reader = ChainStorage {
+        provider,
+        address: args.address,
+        block: args.block,
+        cache: RefCell::default(),
+    };
+
+    let paths: Vec<String> = match args.paths.is_empty() {
+        true => layout.storage.iter().map(|x| x.label.clone()).collect(),
+        false => args.paths.clone(),
+    };
+    for path in &paths {
+        let (location, ty) = layout.resolve(path)?;
+        let decoded = layout
+            .decode(&reader, location, ty)
+            .await
+            .wrap_err_with(|| eyre!("failed to decode {path}"))?;
+        let slot = format!("(slot {:#x})", location.slot);
+        print(&format!("{} {}", path.lavender(), slot.grey()), &decoded, 0);
+    }
+    Ok(())
+}
+
+fn print(label: &str, decoded: &Decoded, depth: usize) {
+    let indent = "  ".repeat(depth);
+    match decoded {
+        Decoded::Value(value) => println!("{indent}{label}: {value}"),
+        Decoded::Mapping(ty) => println!(
+            "{indent}{label}: {}",
+            format!("{ty}, index with [key]").grey()
+        ),
+        Decoded::Array { len, items } => {
+            println!("{indent}{label}: {len} items");
+            for (index, item) in items.iter().enumerate() {
+                print(&format!("[{index}]").lavender(), item, depth + 1);
+            }
+            if items.len() < *len {
+                let more = format!("… {} more", len - items.len());
+                println!("{indent}  {}", more.grey());
+            }
+        }
+        Decoded::Struct(fields) => {
+            println!("{indent}{label}:");
+            for (name, field) in fields {
+                print(&name.lavender(), field, depth + 1);
+            }
+        }
+    }
+}
+
+impl StorageLayout {
+    /// Loads a layout from a file holding either the layout itself or an object with a
+    /// `storageLayout` field, such as a contract's solc output.
+    pub fn load(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .wrap_err_with(|| eyre!("failed to read {}", path.display()))?;
+        let mut json: Value = serde_json::from_str(&data)
+            .wrap_err_with(|| eyre!("failed to parse {}", path.display()))?;
+        if let Some(layout) = json.get_mut("storageLayout") {
+            json = layout.take();
+        }
+        serde_json::from_value(json).wrap_err_with(|| eyre!("invalid layout {}", path.display()))
+    }
+
+    pub fn ty(&self, name: &str) -> Result<&StorageType> {
+        self.types
+            .get(name)
+            .ok_or_else(|| eyre!("layout has no type {name}"))
+    }
+
+    /// Resolves a path like `balances[0xdead].amount` to its location and type.
+    pub fn resolve<'a>(&'a self, path: &str) -> Result<(Location, &'a str)> {
+        let (label, accessors) = parse_path(path)?;
+        let entry = self
+            .storage
+            .iter()
+            .find(|x| x.label == label)
+            .ok_or_else(|| eyre!("layout has no variable {label}"))?;
+        let mut location = Location {
+            slot: entry.slot()?,
+            offset: entry.offset,
+        };
+        let mut name = entry.ty.as_str();
+
+        for accessor in accessors {
+            let ty = self.ty(name)?;
+            match (accessor, ty.encoding.as_str()) {
+                (Accessor::Index(key), "mapping") => {
+                    let key_ty = self.ty(ty.key.as_deref().ok_or_eyre("mapping has no key")?)?;
+                    let mut preimage = encode_key(&key_ty.label, key)?;
+                    preimage.extend(location.slot.to_be_bytes::<32>());
+                    location = Location {
+                        slot: keccak256(preimage).into(),
+                        offset: 0,
+                    };
+                    name = ty.value.as_deref().ok_or_eyre("mapping has no value")?;
+                }
+                (Accessor::Index(index), "dynamic_array" | "inplace") if ty.base.is_some() => {
+                    let index: usize = index
+                        .parse()
+                        .wrap_err_with(|| eyre!("invalid index {index}"))?;
+                    let base = ty.base.as_deref().unwrap();
+                    let start = match ty.encoding.as_str() {
+                        "dynamic_array" => keccak256(location.slot.to_be_bytes::<32>()).into(),
+                        _ => {
+                            let len = static_len(&ty.label)?;
+                            if index >= len {
+                                bail!("index {index} out of bounds for {}", ty.label);
+                            }
+                            location.slot
+                        }
+                    };
+                    location = element(start, index, self.ty(base)?.size()?);
+                    name = base;
+                }
+                (Accessor::Member(member), _) if ty.members.is_some() => {
+                    let members = ty.members.as_ref().unwrap();
+                    let entry = members
+                        .iter()
+                        .find(|x| x.label == member)
+                        .ok_or_else(|| eyre!("{} has no member {member}", ty.label))?;
+                    location = Location {
+                        slot: location.slot.wrapping_add(entry.slot()?),
+                        offset: entry.offset,
+                    };
+                    name = &entry.ty;
+                }
+                (Accessor::Index(_), _) => bail!("{} cannot be indexed", ty.label),
+                (Accessor::Member(member), _) => bail!("{} has no member {member}", ty.label),
+            }
+        }
+        Ok((location, name))
+    }
+
+    /// Reads and decodes the value of the given type at a location.
+    fn decode<'a, R: SlotReader>(
+        &'a self,
+        reader: &'a R,
+        location: Location,
+        name: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Decoded>> + 'a>> {
+        Box::pin(async move {
+            let ty = self.ty(name)?;
+            let decoded = match ty.encoding.as_str() {
+                "inplace" => match (&ty.members, &ty.base) {
+                    (Some(members), _) => {
+                        let mut fields = vec![];
+                        for member in members {
+                            let location = Location {
+                                slot: location.slot.wrapping_add(member.slot()?),
+                                offset: member.offset,
+                            };
+                            let field = self.decode(reader, location, &member.ty).await?;
+                            fields.push((member.label.clone(), field));
+                        }
+                        Decoded::Struct(fields)
+                    }
+                    (None, Some(base)) => {
+                        let len = static_len(&ty.label)?;
+                        let items = self
+                            .decode_elements(reader, location.slot, base, len)
+                            .await?;
+                        Decoded::Array { len, items }
+                    }
+                    (None, None) => {
+                        let word = reader.read(location.slot).await?;
+                        Decoded::Value(decode_value(&ty.label, word, location.offset, ty.size()?)?)
+                    }
+                },
+                "mapping" => Decoded::Mapping(ty.label.clone()),
+                "dynamic_array" => {
+                    let base = ty.base.as_deref().ok_or_eyre("array has no base type")?;
+                    let word = reader.read(location.slot).await?;
+                    let len = U256::from_be_bytes(word.0).saturating_to();
+                    let start = keccak256(location.slot.to_be_bytes::<32>()).into();
+                    let items = self.decode_elements(reader, start, base, len).await?;
+                    Decoded::Array { len, items }
+                }
+                "bytes" => {
+                    let (data, len) = read_bytes(reader, location.slot).await?;
+                    let mut value = match ty.label.as_str() {
+                        "string" => format!("{:?}", String::from_utf8_lossy(&data)),
+                        _ => hex::encode_prefixed(&data),
+                    };
+                    if data.len() < len {
+                        value += &format!(" (first {} of {len} bytes)", data.len());
+                    }
+                    Decoded::Value(value)
+                }
+                other => bail!("unsupported encoding {other} for {}", ty.label),
+            };
+            Ok(decoded)
+        })
+    }
+
+    async fn decode_elements<R: SlotReader>(
+        &self,
+        reader: &R,
+        start: U256,
+        base: &str,
+        len: usize,
+    ) -> Result<Vec<Decoded>> {
+        let size = self.ty(base)?.size()?;
+        let mut items = vec![];
+        for index in 0..len.min(MAX_ELEMENTS) {
+            let location = element(start, index, size);
+            items.push(self.decode(reader, location, base).await?);
+        }
+        Ok(items)
+    }
+}
+
+impl StorageEntry {
+    pub fn slot(&self) -> Result<U256> {
+        self.slot
+            .parse()
+            .wrap_err_with(|| eyre!("invalid slot {} for {}", self.slot, self.label))
+    }
+}
+
+impl StorageType {
+    pub fn size(&self) -> Result<usize> {
+        self.number_of_bytes
+            .parse()
+            .wrap_err_with(|| eyre!("invalid size {} for {}", self.number_of_bytes, self.label))
+    }
+}
+
+/// Splits a path like `balances[0xdead].amount` into its variable and accessors.
+fn parse_path(path: &str) -> Result<(&str, Vec<Accessor<'_>>)> {
+    let end = path.find(['[', '.']).unwrap_or(path.len());
+    let (label, mut rest) = path.split_at(end);
+    if label.is_empty() {
+        bail!("path {path} does not start with a variable");
+    }
+    let mut accessors = vec![];
+    while !rest.is_empty() {
+        if let Some(tail) = rest.strip_prefix('[') {
+            let close = tail
+                .find(']')
+                .ok_or_else(|| eyre!("unclosed [ in {path}"))?;
+            accessors.push(Accessor::Index(&tail[..close]));
+            rest = &tail[close + 1..];
+        } else if let Some(tail) = rest.strip_prefix('.') {
+            let end = tail.find(['[', '.']).unwrap_or(tail.len());
+            accessors.push(Accessor::Member(&tail[..end]));
+            rest = &tail[end..];
+        } else {
+            bail!("unexpected {rest} in {path}");
+        }
+    }
+    Ok((label, accessors))
+}
+
+/// The location of an element of an array starting at the given slot, packing small elements
+/// into shared slots the way solc does.
+fn element(start: U256, index: usize, size: usize) -> Location {
+    match size {
+        0..=32 => {
+            let per_slot = 32 / size.max(1);
+            Location {
+                slot: start.wrapping_add(U256::from(index / per_slot)),
+                offset: (index % per_slot) * size,
+            }
+        }
+        _ => Location {
+            slot: start.wrapping_add(U256::from(index * size.div_ceil(32))),
+            offset: 0,
+        },
+    }
+}
+
+/// The length of a static array, from a label like `uint256[3]`.
+fn static_len(label: &str) -> Result<usize> {
+    label
+        .strip_suffix(']')
+        .and_then(|x| x.rsplit_once('['))
+        .and_then(|(_, len)| len.parse().ok())
+        .ok_or_else(|| eyre!("{label} is not a static array"))
+}
+
+/// The ABI type of a value type, as labeled by solc.
+fn value_type(label: &str) -> Result<DynSolType> {
+    let label = label.strip_suffix(" payable").unwrap_or(label);
+    if label.starts_with("contract ") {
+        return Ok(DynSolType::Address);
+    }
+    if label.starts_with("enum ") {
+        return Ok(DynSolType::Uint(8));
+    }
+    DynSolType::parse(label).wrap_err_with(|| eyre!("unsupported type {label}"))
+}
+
+/// Encodes a mapping key the way solc hashes it: padded for value types and raw for `string`
+/// and `bytes`.
+fn encode_key(label: &str, key: &str) -> Result<Vec<u8>> {
+    match label {
+        "string" => Ok(key.as_bytes().to_vec()),
+        "bytes" => Ok(hex::decode(key).wrap_err_with(|| eyre!("invalid bytes key {key}"))?),
+        _ => {
+            let value = value_type(label)?
+                .coerce_str(key)
+                .wrap_err_with(|| eyre!("invalid {label} key {key}"))?;
+            Ok(value.abi_encode())
+        }
+    }
+}
+
+/// Decodes a value type of `size` bytes packed at `offset` bytes from the end of a slot.
+fn decode_value(label: &str, word: B256, offset: usize, size: usize) -> Result<String> {
+    if size == 0 || offset + size > 32 {
+        bail!("{label} of {size} bytes does not fit at offset {offset}");
+    }
+    let ty = value_type(label)?;
+    let bytes = &word[32 - offset - size..32 - offset];
+    let mut padded = [0; 32];
+    match ty {
+        DynSolType::FixedBytes(_) => padded[..size].copy_from_slice(bytes),
+        DynSolType::Int(_) if bytes[0] & 0x80 != 0 => {
+            padded = [0xff; 32];
+            padded[32 - size..].copy_from_slice(bytes);
+        }
+        _ => padded[32 - size..].copy_from_slice(bytes),
+    }
+    let value = ty
+        .abi_decode(&padded)
+        .wrap_err_with(|| eyre!("invalid {label}"))?;
+    Ok(format_value(&value))
+}
+
+/// Reads a `bytes` or `string`, which is stored inline when shorter than 32 bytes and at the hash
+/// of its slot otherwise. Returns the data read and the full length.
+async fn read_bytes<R: SlotReader>(reader: &R, slot: U256) -> Result<(Vec<u8>, usize)> {
+    let word = reader.read(slot).await?;
+    if word[31] & 1 == 0 {
+        let len = (word[31] / 2) as usize;
+        return Ok((word[..len.min(31)].to_vec(), len));
+    }
+    let len: usize =
+        ((U256::from_be_bytes(word.0) - U256::from(1)) / U256::from(2)).saturating_to();
+    let start: U256 = keccak256(slot.to_be_bytes::<32>()).into();
+    let mut data = vec![];
+    for index in 0..len.div_ceil(32).min(MAX_BYTES_SLOTS) {
+        data.extend(reader.read(start.wrapping_add(U256::from(index))).await?);
+    }
+    data.truncate(len);
+    Ok((data, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::address;
+
+    impl SlotReader for HashMap<U256, B256> {
+        async fn read(&self, slot: U256) -> Result<B256> {
+            Ok(self.get(&slot).copied().unwrap_or_default())
+        }
+    }
+
+    const LAYOUT: &str = r#"{
+      "storageLayout": {
+        "storage": [
+          {"label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+          {"label": "paused", "offset": 20, "slot": "0", "type": "t_bool"},
+          {"label": "delta", "offset": 21, "slot": "0", "type": "t_int16"},
+          {"label": "balances", "offset": 0, "slot": "1", "type": "t_mapping(t_address,t_uint256)"},
+          {"label": "items", "offset": 0, "slot": "2", "type": "t_array(t_uint128)dyn_storage"},
+          {"label": "name", "offset": 0, "slot": "3", "type": "t_string_storage"},
+          {"label": "point", "offset": 0, "slot": "4", "type": "t_struct(Point)_storage"}
+        ],
+        "types": {
+          "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+          "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+          "t_int16": {"encoding": "inplace", "label": "int16", "numberOfBytes": "2"},
+          "t_uint128": {"encoding": "inplace", "label": "uint128", "numberOfBytes": "16"},
+          "t_uint256": {"encoding": "inplace", "label": "uint256", "numberOfBytes": "32"},
+          "t_mapping(t_address,t_uint256)": {"encoding": "mapping", "key": "t_address", "label": "mapping(address => uint256)", "numberOfBytes": "32", "value": "t_uint256"},
+          "t_array(t_uint128)dyn_storage": {"encoding": "dynamic_array", "base": "t_uint128", "label": "uint128[]", "numberOfBytes": "32"},
+          "t_string_storage": {"encoding": "bytes", "label": "string", "numberOfBytes": "32"},
+          "t_struct(Point)_storage": {"encoding": "inplace", "label": "struct Point", "numberOfBytes": "64", "members": [
+            {"label": "x", "offset": 0, "slot": "0", "type": "t_uint256"},
+            {"label": "y", "offset": 0, "slot": "1", "type": "t_uint256"}
+          ]}
+        }
+      }
+    }"#;
+
+    #[tokio::test]
+    async fn decode_layout() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        fs::write(file.path(), LAYOUT).unwrap();
+        let layout = StorageLayout::load(file.path()).unwrap();
+
+        let owner = address!("000000000000000000000000000000000000dEaD");
+        let mut slot0 = [0u8; 32];
+        slot0[12..].copy_from_slice(owner.as_slice());
+        slot0[11] = 1;
+        slot0[9..11].copy_from_slice(&(-2i16).to_be_bytes());
+
+        let (balance, ty) = layout.resolve(&format!("balances[{owner}]")).unwrap();
+        let mut preimage = [0u8; 64];
+        preimage[12..32].copy_from_slice(owner.as_slice());
+        preimage[63] = 1;
+        assert_eq!(balance.slot, U256::from_be_bytes(keccak256(preimage).0));
+        assert_eq!(ty, "t_uint256");
+
+        let (second, _) = layout.resolve("items[1]").unwrap();
+        let items: U256 = keccak256(U256::from(2).to_be_bytes::<32>()).into();
+        assert_eq!(
+            second,
+            Location {
+                slot: items,
+                offset: 16
+            }
+        );
+        assert_eq!(layout.resolve("point.y").unwrap().0.slot, U256::from(5));
+        assert!(layout.resolve("owner[0]").is_err());
+        assert!(layout.resolve("point.z").is_err());
+
+        let mut name = [0u8; 32];
+        name[..5].copy_from_slice(b"hello");
+        name[31] = 10;
+        let mut packed = [0u8; 32];
+        packed[15] = 7;
+        packed[31] = 3;
+
+        let storage = HashMap::from([
+            (U256::ZERO, B256::from(slot0)),
+            (balance.slot, B256::from(U256::from(1000))),
+            (U256::from(2), B256::from(U256::from(2))),
+            (items, B256::from(packed)),
+            (U256::from(3), B256::from(name)),
+            (U256::from(5), B256::from(U256::from(9))),
+        ]);
+        let read = |path: &str| {
+            let (location, ty) = layout.resolve(path).unwrap();
+            let layout = &layout;
+            let storage = &storage;
+            async move { layout.decode(storage, location, ty).await.unwrap() }
+        };
+        let value = |x: &str| Decoded::Value(x.to_owned());
+
+        assert_eq!(read("owner").await, value(&owner.to_string()));
+        assert_eq!(read("paused").await, value("true"));
+        assert_eq!(read("delta").await, value("-2"));
+        assert_eq!(read(&format!("balances[{owner}]")).await, value("1000"));
+        assert_eq!(
+            read("balances").await,
+            Decoded::Mapping("mapping(address => uint256)".to_owned())
+        );
+        assert_eq!(
+            read("items").await,
+            Decoded::Array {
+                len: 2,
+                items: vec![value("3"), value("7")]
+            }
+        );
+        assert_eq!(read("name").await, value("\"hello\""));
+        assert_eq!(
+            read("point").await,
+            Decoded::Struct(vec![
+                ("x".to_owned(), value("0")),
+                ("y".to_owned(), value("9"))
+            ])
+        );
+    }
+}
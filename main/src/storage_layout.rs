@@ -0,0 +1,545 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Computes the storage layout of a project's entrypoint from its `#[storage]` and `sol_storage!`
+//! declarations, in the format of solc's `storageLayout` output.
+
+use crate::{
+    storage::{StorageEntry, StorageLayout, StorageType},
+    util::sys,
+    ExportStorageLayoutArgs,
+};
+use eyre::{bail, eyre, OptionExt, Result, WrapErr};
+use std::{collections::BTreeMap, fs, io::Write, path::Path};
+use syn::{Attribute, Expr, GenericArgument, Item, Lit, PathArguments, Type};
+
+/// A storage type, as declared in Rust or in `sol_storage!`.
+#[derive(Clone, Debug, PartialEq)]
+enum StorageTy {
+    /// A value type, packed with its neighbors when they fit in a slot.
+    Value {
+        label: String,
+        size: usize,
+    },
+    /// A `string` or `bytes`.
+    Bytes(&'static str),
+    Mapping(Box<StorageTy>, Box<StorageTy>),
+    Vec(Box<StorageTy>),
+    Array(Box<StorageTy>, usize),
+    Struct(String),
+}
+
+/// A struct declared with `#[storage]` or in `sol_storage!`.
+struct StorageStruct {
+    entrypoint: bool,
+    fields: Vec<(String, StorageTy)>,
+}
+
+pub fn export_storage_layout(args: &ExportStorageLayoutArgs) -> Result<()> {
+    let layout = project_layout(Path::new("."))?;
+    let mut out = sys::file_or_stdout(args.output.clone())?;
+    serde_json::to_writer_pretty(&mut out, &layout)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Computes the storage layout of the entrypoint of the project in the given directory.
+pub fn project_layout(dir: &Path) -> Result<StorageLayout> {
+    let structs = project_structs(dir)?;
+    layout(entrypoint(&structs)?, &structs)
+}
+
+/// Finds the name of the `#[entrypoint]` struct of the project in the given directory.
+pub fn entrypoint_name(dir: &Path) -> Result<String> {
+    let structs = project_structs(dir)?;
+    entrypoint(&structs).map(str::to_owned)
+}
+
+fn project_structs(dir: &Path) -> Result<BTreeMap<String, StorageStruct>> {
+    let mut structs = BTreeMap::new();
+    let pattern = dir.join("src").join("**").join("*.rs");
+    for path in glob::glob(&pattern.to_string_lossy())? {
+        let path = path?;
+        let source = fs::read_to_string(&path)?;
+        let file = syn::parse_file(&source)
+            .wrap_err_with(|| eyre!("failed to parse {}", path.display()))?;
+        collect_structs(&file.items, &mut structs)
+            .wrap_err_with(|| eyre!("failed to read storage of {}", path.display()))?;
+    }
+    Ok(structs)
+}
+
+fn entrypoint(structs: &BTreeMap<String, StorageStruct>) -> Result<&str> {
+    let entrypoints: Vec<_> = structs
+        .iter()
+        .filter(|(_, x)| x.entrypoint)
+        .map(|(name, _)| name.as_str())
+        .collect();
+    let [entrypoint] = entrypoints[..] else {
+        bail!(
+            "expected one #[entrypoint] storage struct, found {}",
+            entrypoints.len()
+        );
+    };
+    Ok(entrypoint)
+}
+
+fn layout(entrypoint: &str, structs: &BTreeMap<String, StorageStruct>) -> Result<StorageLayout> {
+    let mut builder = Builder {
+        structs,
+        types: BTreeMap::new(),
+        stack: vec![],
+    };
+    let (storage, _) = builder.fields(entrypoint)?;
+    Ok(StorageLayout {
+        storage,
+        types: builder.types,
+    })
+}
+
+fn has_attr(attrs: &[Attribute], name: &str) -> bool {
+    attrs
+        .iter()
+        .any(|attr| attr.path().segments.last().is_some_and(|x| x.ident == name))
+}
+
+fn collect_structs(items: &[Item], structs: &mut BTreeMap<String, StorageStruct>) -> Result<()> {
+    for item in items {
+        match item {
+            Item::Struct(item)
+                if has_attr(&item.attrs, "storage")
+                    || has_attr(&item.attrs, "solidity_storage") =>
+            {
+                let mut fields = vec![];
+                for (index, field) in item.fields.iter().enumerate() {
+                    let name = match &field.ident {
+                        Some(ident) => ident.to_string(),
+                        None => index.to_string(),
+                    };
+                    let ty = rust_type(&field.ty)
+                        .wrap_err_with(|| eyre!("unsupported type of {}.{name}", item.ident))?;
+                    fields.push((name, ty));
+                }
+                let entrypoint = has_attr(&item.attrs, "entrypoint");
+                structs.insert(item.ident.to_string(), StorageStruct { entrypoint, fields });
+            }
+            Item::Macro(item)
+                if item
+                    .mac
+                    .path
+                    .segments
+                    .last()
+                    .is_some_and(|x| x.ident == "sol_storage") =>
+            {
+                sol_storage(&item.mac.tokens.to_string(), structs)?;
+            }
+            Item::Mod(item) => {
+                if let Some((_, items)) = &item.content {
+                    collect_structs(items, structs)?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn value(label: String, size: usize) -> StorageTy {
+    StorageTy::Value { label, size }
+}
+
+fn uint(bits: usize) -> Result<StorageTy> {
+    if bits == 0 || bits > 256 || !bits.is_multiple_of(8) {
+        bail!("invalid integer size {bits}");
+    }
+    Ok(value(format!("uint{bits}"), bits / 8))
+}
+
+fn int(bits: usize) -> Result<StorageTy> {
+    if bits == 0 || bits > 256 || !bits.is_multiple_of(8) {
+        bail!("invalid integer size {bits}");
+    }
+    Ok(value(format!("int{bits}"), bits / 8))
+}
+
+fn fixed_bytes(size: usize) -> Result<StorageTy> {
+    if size == 0 || size > 32 {
+        bail!("invalid fixed bytes size {size}");
+    }
+    Ok(value(format!("bytes{size}"), size))
+}
+
+/// Parses a number suffixing a type name, like the `64` of `StorageU64`.
+fn suffix(name: &str, prefix: &str) -> Option<usize> {
+    name.strip_prefix(prefix)?.parse().ok()
+}
+
+/// The type of a field of a `#[storage]` struct.
+fn rust_type(ty: &Type) -> Result<StorageTy> {
+    let (name, args) = path_type(ty)?;
+    let ty_arg = |index: usize| match args.get(index) {
+        Some(GenericArgument::Type(ty)) => Ok(ty),
+        _ => Err(eyre!("{name} expects a type argument")),
+    };
+    let const_arg = |index: usize| const_arg(&name, args.get(index).copied());
+
+    Ok(match name.as_str() {
+        "StorageBool" => value("bool".into(), 1),
+        "StorageAddress" => value("address".into(), 20),
+        "StorageBlockNumber" => uint(64)?,
+        "StorageBlockHash" => fixed_bytes(32)?,
+        "StorageString" => StorageTy::Bytes("string"),
+        "StorageBytes" => StorageTy::Bytes("bytes"),
+        "StorageUint" => uint(const_arg(0)?)?,
+        "StorageSigned" => int(const_arg(0)?)?,
+        "StorageFixedBytes" => fixed_bytes(const_arg(0)?)?,
+        "StorageMap" => StorageTy::Mapping(
+            Box::new(key_type(ty_arg(0)?)?),
+            Box::new(rust_type(ty_arg(1)?)?),
+        ),
+        "StorageVec" => StorageTy::Vec(Box::new(rust_type(ty_arg(0)?)?)),
+        "StorageArray" => StorageTy::Array(Box::new(rust_type(ty_arg(0)?)?), const_arg(1)?),
+        name => {
+            if let Some(bits) = suffix(name, "StorageU") {
+                uint(bits)?
+            } else if let Some(bits) = suffix(name, "StorageI") {
+                int(bits)?
+            } else if let Some(bits) = suffix(name, "StorageB") {
+                fixed_bytes(bits / 8)?
+            } else {
+                StorageTy::Struct(name.to_owned())
+            }
+        }
+    })
+}
+
+/// The type of a key of a `StorageMap`.
+fn key_type(ty: &Type) -> Result<StorageTy> {
+    let (name, args) = path_type(ty)?;
+    let const_arg = |index: usize| const_arg(&name, args.get(index).copied());
+    Ok(match name.as_str() {
+        "Address" => value("address".into(), 20),
+        "bool" => value("bool".into(), 1),
+        "String" => StorageTy::Bytes("string"),
+        "Bytes" | "Vec" => StorageTy::Bytes("bytes"),
+        "Uint" => uint(const_arg(0)?)?,
+        "Signed" => int(const_arg(0)?)?,
+        "FixedBytes" => fixed_bytes(const_arg(0)?)?,
+        name => {
+            if let Some(bits) = suffix(name, "U").or_else(|| suffix(name, "u")) {
+                uint(bits)?
+            } else if let Some(bits) = suffix(name, "I").or_else(|| suffix(name, "i")) {
+                int(bits)?
+            } else if let Some(bits) = suffix(name, "B") {
+                fixed_bytes(bits / 8)?
+            } else {
+                bail!("unsupported key type {name}");
+            }
+        }
+    })
+}
+
+/// The name and generic arguments of a type like `StorageMap<Address, StorageU256>`.
+fn path_type(ty: &Type) -> Result<(String, Vec<&GenericArgument>)> {
+    let Type::Path(path) = ty else {
+        bail!("expected a named type");
+    };
+    let segment = path.path.segments.last().ok_or_eyre("empty type")?;
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().collect(),
+        _ => vec![],
+    };
+    Ok((segment.ident.to_string(), args))
+}
+
+fn const_arg(name: &str, arg: Option<&GenericArgument>) -> Result<usize> {
+    match arg {
+        Some(GenericArgument::Const(Expr::Lit(expr))) => match &expr.lit {
+            Lit::Int(int) => Ok(int.base10_parse()?),
+            _ => bail!("{name} expects an integer argument"),
+        },
+        _ => bail!("{name} expects a literal integer argument"),
+    }
+}
+
+/// Reads the structs declared in the body of a `sol_storage!` macro.
+fn sol_storage(source: &str, structs: &mut BTreeMap<String, StorageStruct>) -> Result<()> {
+    let mut rest = source;
+    while let Some(start) = rest.find("struct ") {
+        let entrypoint = rest[..start].contains("entrypoint");
+        let tail = &rest[start + "struct ".len()..];
+        let (Some(open), Some(close)) = (tail.find('{'), tail.find('}')) else {
+            bail!("unterminated struct in sol_storage!");
+        };
+        let name = tail[..open].trim().to_owned();
+
+        let mut fields = vec![];
+        for field in tail[open + 1..close].split(';') {
+            let mut field = field.trim();
+            // skip attributes like `#[borrow]`
+            while let Some(attr) = field.strip_prefix('#') {
+                let end = attr.find(']').ok_or_eyre("unterminated attribute")?;
+                field = attr[end + 1..].trim();
+            }
+            if field.is_empty() {
+                continue;
+            }
+            let Some((ty, label)) = field.rsplit_once(char::is_whitespace) else {
+                bail!("invalid field {field} in {name}");
+            };
+            let ty: String = ty.split_whitespace().collect();
+            let ty =
+                solidity_type(&ty).wrap_err_with(|| eyre!("unsupported type of {name}.{label}"))?;
+            fields.push((label.to_owned(), ty));
+        }
+        structs.insert(name, StorageStruct { entrypoint, fields });
+        rest = &tail[close + 1..];
+    }
+    Ok(())
+}
+
+/// The type of a field declared in `sol_storage!`, like `mapping(address=>uint256[])`.
+fn solidity_type(ty: &str) -> Result<StorageTy> {
+    if let Some(inner) = ty.strip_suffix(']') {
+        let open = inner.rfind('[').ok_or_eyre("unbalanced brackets")?;
+        let base = Box::new(solidity_type(&inner[..open])?);
+        return Ok(match &inner[open + 1..] {
+            "" => StorageTy::Vec(base),
+            len => StorageTy::Array(base, len.parse()?),
+        });
+    }
+    if let Some(inner) = ty
+        .strip_prefix("mapping(")
+        .and_then(|x| x.strip_suffix(')'))
+    {
+        let (key, value) = inner.split_once("=>").ok_or_eyre("mapping has no =>")?;
+        return Ok(StorageTy::Mapping(
+            Box::new(solidity_type(key)?),
+            Box::new(solidity_type(value)?),
+        ));
+    }
+    Ok(match ty {
+        "bool" => value("bool".into(), 1),
+        "address" => value("address".into(), 20),
+        "string" => StorageTy::Bytes("string"),
+        "bytes" => StorageTy::Bytes("bytes"),
+        "uint" => uint(256)?,
+        "int" => int(256)?,
+        ty => {
+            if let Some(bits) = suffix(ty, "uint") {
+                uint(bits)?
+            } else if let Some(bits) = suffix(ty, "int") {
+                int(bits)?
+            } else if let Some(size) = suffix(ty, "bytes") {
+                fixed_bytes(size)?
+            } else {
+                StorageTy::Struct(ty.to_owned())
+            }
+        }
+    })
+}
+
+/// Lays out structs the way solc does, registering each type it encounters.
+struct Builder<'a> {
+    structs: &'a BTreeMap<String, StorageStruct>,
+    types: BTreeMap<String, StorageType>,
+    /// The structs being laid out, to detect recursive ones.
+    stack: Vec<String>,
+}
+
+impl Builder<'_> {
+    /// Lays out the fields of a struct, returning their entries and the number of slots taken.
+    fn fields(&mut self, name: &str) -> Result<(Vec<StorageEntry>, usize)> {
+        if self.stack.iter().any(|x| x == name) {
+            bail!("storage struct {name} contains itself");
+        }
+        let structs = self.structs;
+        let def = structs
+            .get(name)
+            .ok_or_else(|| eyre!("unknown storage type {name}"))?;
+        self.stack.push(name.to_owned());
+
+        let mut entries = vec![];
+        let (mut slot, mut offset) = (0, 0);
+        for (label, ty) in &def.fields {
+            let (id, size) = self.register(ty)?;
+            let packed = matches!(ty, StorageTy::Value { .. });
+            if offset > 0 && (!packed || offset + size > 32) {
+                slot += 1;
+                offset = 0;
+            }
+            entries.push(StorageEntry {
+                label: label.clone(),
+                slot: slot.to_string(),
+                offset,
+                ty: id,
+            });
+            match packed {
+                true => offset += size,
+                false => slot += size.div_ceil(32),
+            }
+        }
+        self.stack.pop();
+        Ok((entries, slot + (offset > 0) as usize))
+    }
+
+    /// Registers a type and those it contains, returning its id and size in bytes.
+    fn register(&mut self, ty: &StorageTy) -> Result<(String, usize)> {
+        let label = |types: &BTreeMap<String, StorageType>, id: &str| types[id].label.clone();
+        let (id, entry) = match ty {
+            StorageTy::Value { label, size } => (
+                format!("t_{label}"),
+                StorageType {
+                    encoding: "inplace".into(),
+                    label: label.clone(),
+                    number_of_bytes: size.to_string(),
+                    ..Default::default()
+                },
+            ),
+            StorageTy::Bytes(label) => (
+                format!("t_{label}_storage"),
+                StorageType {
+                    encoding: "bytes".into(),
+                    label: label.to_string(),
+                    number_of_bytes: "32".into(),
+                    ..Default::default()
+                },
+            ),
+            StorageTy::Mapping(key, value) => {
+                let (key, _) = self.register(key)?;
+                let (value, _) = self.register(value)?;
+                let entry = StorageType {
+                    encoding: "mapping".into(),
+                    label: format!(
+                        "mapping({} => {})",
+                        label(&self.types, &key),
+                        label(&self.types, &value)
+                    ),
+                    number_of_bytes: "32".into(),
+                    key: Some(key.clone()),
+                    value: Some(value.clone()),
+                    ..Default::default()
+                };
+                (format!("t_mapping({key},{value})"), entry)
+            }
+            StorageTy::Vec(base) => {
+                let (base, _) = self.register(base)?;
+                let entry = StorageType {
+                    encoding: "dynamic_array".into(),
+                    label: format!("{}[]", label(&self.types, &base)),
+                    number_of_bytes: "32".into(),
+                    base: Some(base.clone()),
+                    ..Default::default()
+                };
+                (format!("t_array({base})dyn_storage"), entry)
+            }
+            StorageTy::Array(base, len) => {
+                let (base, size) = self.register(base)?;
+                let slots = match size {
+                    0..=32 => len.div_ceil(32 / size.max(1)),
+                    _ => len * size.div_ceil(32),
+                };
+                let entry = StorageType {
+                    encoding: "inplace".into(),
+                    label: format!("{}[{len}]", label(&self.types, &base)),
+                    number_of_bytes: (slots * 32).to_string(),
+                    base: Some(base.clone()),
+                    ..Default::default()
+                };
+                (format!("t_array({base}){len}_storage"), entry)
+            }
+            StorageTy::Struct(name) => {
+                let (members, slots) = self.fields(name)?;
+                let entry = StorageType {
+                    encoding: "inplace".into(),
+                    label: format!("struct {name}"),
+                    number_of_bytes: (slots * 32).to_string(),
+                    members: Some(members),
+                    ..Default::default()
+                };
+                (format!("t_struct({name})_storage"), entry)
+            }
+        };
+        let size = entry.size()?;
+        self.types.insert(id.clone(), entry);
+        Ok((id, size))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_storage_structs() {
+        let source = r#"
+            sol_storage! {
+                pub struct Position {
+                    uint128 amount;
+                    uint64 since;
+                    bool active;
+                }
+            }
+
+            #[storage]
+            #[entrypoint]
+            pub struct Vault {
+                owner: StorageAddress,
+                paused: StorageBool,
+                fee: StorageU16,
+                total: StorageU256,
+                positions: StorageMap<Address, Position>,
+                history: StorageVec<StorageU128>,
+                limits: StorageArray<StorageU64, 5>,
+                name: StorageString,
+                config: Config,
+                version: StorageU8,
+            }
+
+            #[storage]
+            pub struct Config {
+                admin: StorageAddress,
+                tags: StorageMap<String, StorageB256>,
+            }
+        "#;
+        let file = syn::parse_file(source).unwrap();
+        let mut structs = BTreeMap::new();
+        collect_structs(&file.items, &mut structs).unwrap();
+        let layout = layout("Vault", &structs).unwrap();
+
+        let slots: Vec<_> = layout
+            .storage
+            .iter()
+            .map(|x| (x.label.as_str(), x.slot.as_str(), x.offset, x.ty.as_str()))
+            .collect();
+        assert_eq!(
+            slots,
+            [
+                ("owner", "0", 0, "t_address"),
+                ("paused", "0", 20, "t_bool"),
+                ("fee", "0", 21, "t_uint16"),
+                ("total", "1", 0, "t_uint256"),
+                (
+                    "positions",
+                    "2",
+                    0,
+                    "t_mapping(t_address,t_struct(Position)_storage)"
+                ),
+                ("history", "3", 0, "t_array(t_uint128)dyn_storage"),
+                ("limits", "4", 0, "t_array(t_uint64)5_storage"),
+                ("name", "6", 0, "t_string_storage"),
+                ("config", "7", 0, "t_struct(Config)_storage"),
+                ("version", "9", 0, "t_uint8"),
+            ]
+        );
+
+        let position = &layout.types["t_struct(Position)_storage"];
+        assert_eq!(position.number_of_bytes, "32");
+        let members = position.members.as_ref().unwrap();
+        assert_eq!((members[2].slot.as_str(), members[2].offset), ("0", 24));
+        assert_eq!(
+            layout.types["t_mapping(t_string_storage,t_bytes32)"].label,
+            "mapping(string => bytes32)"
+        );
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Turns a recorded [`Trace`] into a `stylus-sdk` TestVM unit test, so that a bug found onchain
+//! can be locked in as a regression test.
+
+use crate::trace::{HostioKind::*, TraceFrame};
+use alloy::primitives::{Address, B256, U256};
+use eyre::Result;
+use std::{collections::HashSet, fmt::Write, fs, path::Path};
+
+/// A call made by the contract, mocked with the return data it observed onchain.
+struct MockCall {
+    kind: &'static str,
+    address: Address,
+    data: Box<[u8]>,
+    value: Option<U256>,
+    status: u8,
+    output: Vec<u8>,
+}
+
+/// Writes a test reproducing a call of a trace to `path`, calling the given entrypoint type.
+pub fn emit_test(frame: &TraceFrame, calldata: &[u8], entrypoint: &str, path: &Path) -> Result<()> {
+    let test = render(frame, calldata, entrypoint)?;
+    fs::write(path, test)?;
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    format!("hex!(\"{}\")", alloy::hex::encode(bytes))
+}
+
+fn render(frame: &TraceFrame, calldata: &[u8], entrypoint: &str) -> Result<String> {
+    let mut setup = String::new();
+    let mut written = HashSet::new();
+    let mut mocks: Vec<MockCall> = vec![];
+    let mut logs = vec![];
+    let mut result = vec![];
+    let mut status = 0;
+
+    for step in frame.steps() {
+        match &step.kind {
+            // slots read after being written hold the local value, not the initial one
+            StorageLoadBytes32 { key, value } if written.insert(*key) => {
+                let key = U256::from_be_bytes(key.0);
+                writeln!(
+                    setup,
+                    "    vm.set_storage(U256::from_str_radix(\"{key:x}\", 16).unwrap(), B256::new({}));",
+                    hex(value.as_slice())
+                )?;
+            }
+            StorageCacheBytes32 { key, .. } => {
+                written.insert(*key);
+            }
+            AccountBalance { address, balance } => writeln!(
+                setup,
+                "    vm.set_balance(address!(\"{address:x}\"), U256::from_str_radix(\"{balance:x}\", 16).unwrap());"
+            )?,
+            BlockNumber { number } => writeln!(setup, "    vm.set_block_number({number});")?,
+            BlockTimestamp { timestamp } => {
+                writeln!(setup, "    vm.set_block_timestamp({timestamp});")?
+            }
+            Chainid { chainid } => writeln!(setup, "    vm.set_chain_id({chainid});")?,
+            ContractAddress { address } => writeln!(
+                setup,
+                "    vm.set_contract_address(address!(\"{address:x}\"));"
+            )?,
+            MsgSender { sender } => {
+                writeln!(setup, "    vm.set_sender(address!(\"{sender:x}\"));")?
+            }
+            MsgValue { value } => {
+                let value = U256::from_be_bytes(value.0);
+                writeln!(
+                    setup,
+                    "    vm.set_value(U256::from_str_radix(\"{value:x}\", 16).unwrap());"
+                )?
+            }
+            TxOrigin { origin } => {
+                writeln!(setup, "    vm.set_tx_origin(address!(\"{origin:x}\"));")?
+            }
+            CallContract {
+                address,
+                data,
+                value,
+                status,
+                ..
+            } => mocks.push(MockCall {
+                kind: "mock_call",
+                address: *address,
+                data: data.clone(),
+                value: Some(*value),
+                status: *status,
+                output: vec![],
+            }),
+            DelegateCallContract {
+                address,
+                data,
+                status,
+                ..
+            } => mocks.push(MockCall {
+                kind: "mock_delegate_call",
+                address: *address,
+                data: data.clone(),
+                value: None,
+                status: *status,
+                output: vec![],
+            }),
+            StaticCallContract {
+                address,
+                data,
+                status,
+                ..
+            } => mocks.push(MockCall {
+                kind: "mock_static_call",
+                address: *address,
+                data: data.clone(),
+                value: None,
+                status: *status,
+                output: vec![],
+            }),
+            ReadReturnData { offset, data, .. } => {
+                if let Some(mock) = mocks.last_mut() {
+                    let offset = *offset as usize;
+                    if mock.output.len() < offset + data.len() {
+                        mock.output.resize(offset + data.len(), 0);
+                    }
+                    mock.output[offset..offset + data.len()].copy_from_slice(data);
+                }
+            }
+            EmitLog { data, topics } => {
+                // the trace parser ensures the data covers the topics
+                let (topics, data) = data.split_at(*topics as usize * 32);
+                logs.push((topics.chunks(32).map(B256::from_slice).collect::<Vec<_>>(), data));
+            }
+            WriteResult { result: output } => result = output.to_vec(),
+            UserReturned { status: returned } => status = *returned,
+            ExitEarly { status: exited } => status = *exited,
+            _ => {}
+        }
+    }
+
+    for mock in &mocks {
+        let output = match mock.status {
+            0 => format!("Ok({}.to_vec())", hex(&mock.output)),
+            _ => format!("Err({}.to_vec())", hex(&mock.output)),
+        };
+        let value = match mock.value {
+            Some(value) => format!(" U256::from_str_radix(\"{value:x}\", 16).unwrap(),"),
+            None => String::new(),
+        };
+        writeln!(
+            setup,
+            "    vm.{}(address!(\"{:x}\"), {}.to_vec(),{value} {output});",
+            mock.kind,
+            mock.address,
+            hex(&mock.data),
+        )?;
+    }
+
+    let mut test = String::new();
+    writeln!(test, "// Generated by `cargo stylus trace --emit-test`.")?;
+    writeln!(test, "#![cfg(test)]\n")?;
+    writeln!(test, "use stylus_sdk::{{")?;
+    writeln!(test, "    alloy_primitives::{{address, hex, B256, U256}},")?;
+    writeln!(test, "    testing::*,")?;
+    writeln!(test, "}};\n")?;
+    writeln!(
+        test,
+        "/// The contract's entrypoint, as marked with `#[entrypoint]`."
+    )?;
+    writeln!(test, "type Entrypoint = crate::{entrypoint};\n")?;
+    writeln!(test, "#[test]")?;
+    writeln!(test, "fn replay_onchain_trace() {{")?;
+    writeln!(test, "    let vm = TestVM::default();")?;
+    test.push_str(&setup);
+    writeln!(test)?;
+    writeln!(test, "    let calldata = {}.to_vec();", hex(calldata))?;
+    writeln!(
+        test,
+        "    let result = stylus_sdk::abi::router_entrypoint::<Entrypoint, Entrypoint>(calldata, vm.clone().into());"
+    )?;
+    match status {
+        0 => writeln!(
+            test,
+            "    assert_eq!(result, Ok({}.to_vec()));",
+            hex(&result)
+        )?,
+        _ => writeln!(
+            test,
+            "    assert_eq!(result, Err({}.to_vec()));",
+            hex(&result)
+        )?,
+    }
+
+    writeln!(test, "\n    let logs = vm.get_emitted_logs();")?;
+    writeln!(test, "    assert_eq!(logs.len(), {});", logs.len())?;
+    for (index, (topics, data)) in logs.iter().enumerate() {
+        let topics: Vec<_> = topics
+            .iter()
+            .map(|topic| format!("B256::new({})", hex(topic.as_slice())))
+            .collect();
+        writeln!(
+            test,
+            "    assert_eq!(logs[{index}], (vec![{}], {}.to_vec()));",
+            topics.join(", "),
+            hex(data)
+        )?;
+    }
+    writeln!(test, "}}")?;
+    Ok(test)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_fixture() {
+        let trace = r#"
+        [
+          {
+            "name": "read_args",
+            "args": "0x",
+            "outs": "0xbeef",
+            "startInk": 1000,
+            "endInk": 900
+          },
+          {
+            "name": "storage_load_bytes32",
+            "args": "0x0000000000000000000000000000000000000000000000000000000000000001",
+            "outs": "0x000000000000000000000000000000000000000000000000000000000000002a",
+            "startInk": 900,
+            "endInk": 800
+          },
+          {
+            "name": "block_number",
+            "args": "0x",
+            "outs": "0x0000000000000010",
+            "startInk": 800,
+            "endInk": 700
+          },
+          {
+            "name": "write_result",
+            "args": "0x2a",
+            "outs": "0x",
+            "startInk": 700,
+            "endInk": 600
+          },
+          {
+            "name": "user_returned",
+            "args": "0x",
+            "outs": "0x00000000",
+            "startInk": 600,
+            "endInk": 600
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+
+        let test = render(&frame, &[0xbe, 0xef], "Counter").expect("failed to render");
+        assert!(test.contains("type Entrypoint = crate::Counter;"));
+        assert!(test.contains("vm.set_storage(U256::from_str_radix(\"1\", 16).unwrap(), B256::new(hex!(\"000000000000000000000000000000000000000000000000000000000000002a\")));"));
+        assert!(test.contains("vm.set_block_number(16);"));
+        assert!(test.contains("let calldata = hex!(\"beef\").to_vec();"));
+        assert!(test.contains("assert_eq!(result, Ok(hex!(\"2a\").to_vec()));"));
+        assert!(test.contains("assert_eq!(logs.len(), 0);"));
+    }
+}
@@ -3,20 +3,24 @@
 
 #![allow(clippy::redundant_closure_call)]
 
+use crate::macros::*;
+use crate::util::abi::encode_call;
 use crate::util::color::{Color, DebugColor};
 use crate::SimulateArgs;
 use alloy::eips::BlockId;
 use alloy::network::TransactionBuilder;
 use alloy::primitives::{Address, TxHash, B256, U256};
-use alloy::providers::{ext::DebugApi, Provider};
-use alloy::rpc::types::trace::geth::{GethDebugTracerType, GethDebugTracingOptions};
-use alloy::rpc::types::trace::geth::{GethDebugTracingCallOptions, GethTrace};
-use alloy::rpc::types::TransactionRequest;
+use alloy::providers::Provider;
+use alloy::rpc::types::{state::StateOverride, TransactionRequest};
+use alloy::transports::TransportResult;
 use eyre::{bail, OptionExt, Result, WrapErr};
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use serde_json::{from_value, Value};
+use serde_json::{from_value, json, Value};
 use sneks::SimpleSnakeNames;
-use std::{collections::VecDeque, mem};
+use std::{
+    backtrace::Backtrace, collections::VecDeque, fs, future::Future, mem, path::Path, process,
+};
 
 #[derive(Debug)]
 pub struct Trace {
@@ -40,19 +44,14 @@ impl Trace {
             bail!("failed to get tx data: {}", hash)
         };
 
-        let query = if use_native_tracer {
-            "stylusTracer"
-        } else {
-            include_str!("query.js")
-        };
-        let tracer = GethDebugTracingOptions {
-            tracer: Some(GethDebugTracerType::JsTracer(query.to_owned())),
-            ..GethDebugTracingOptions::default()
-        };
-        let GethTrace::JS(json) = provider.debug_trace_transaction(hash, tracer).await? else {
-            bail!("malformed tracing result")
-        };
-        if let Value::Array(arr) = json.clone() {
+        let json = trace_with(use_native_tracer, |tracer| async move {
+            provider
+                .raw_request("debug_traceTransaction".into(), (hash, tracer.config()))
+                .await
+        })
+        .await?;
+        let normalized = normalize(json.clone())?;
+        if let Value::Array(arr) = &normalized {
             if arr.is_empty() {
                 bail!("No trace frames found, perhaps you are attempting to trace the contract deployment transaction");
             }
@@ -64,7 +63,7 @@ impl Trace {
         }
 
         let to: Option<Address> = receipt.to.map(|x| Address::from(x.0));
-        let top_frame = TraceFrame::parse_frame(to, json.clone())?;
+        let top_frame = TraceFrame::parse_frame(to, normalized)?;
 
         Ok(Self {
             top_frame,
@@ -73,12 +72,32 @@ impl Trace {
         })
     }
 
-    pub fn reader(self) -> FrameReader {
-        FrameReader {
-            steps: self.top_frame.steps.clone().into(),
-            frame: self.top_frame,
-        }
+    /// Loads a trace previously written with [`Trace::save`], without contacting a node.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let data = fs::read(path)
+            .wrap_err_with(|| format!("failed to read trace file {}", path.to_string_lossy()))?;
+        let TraceFile { to, tx, json } =
+            serde_json::from_slice(&data).wrap_err("failed to parse trace file")?;
+        let top_frame = TraceFrame::parse_frame(to, normalize(json.clone())?)?;
+        Ok(Self {
+            top_frame,
+            tx,
+            json,
+        })
+    }
+
+    /// Persists the transaction request and the raw tracer output to disk.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = TraceFile {
+            to: self.top_frame.address,
+            tx: self.tx.clone(),
+            json: self.json.clone(),
+        };
+        let data = serde_json::to_vec_pretty(&file)?;
+        fs::write(path, data)
+            .wrap_err_with(|| format!("failed to write trace file {}", path.to_string_lossy()))
     }
+
     pub async fn simulate(provider: &impl Provider, args: &SimulateArgs) -> Result<Self> {
         // Build the transaction request
         let mut tx_request = TransactionRequest::default();
@@ -101,40 +120,65 @@ impl Trace {
         if let Some(data) = &args.data {
             tx_request = tx_request.with_input(data.clone());
         }
+        if let Some(sig) = &args.sig {
+            tx_request = tx_request.with_input(encode_call(sig, &args.args)?);
+        }
 
-        // Use the same tracer as in Trace::new
-        let query = if args.use_native_tracer {
-            "stylusTracer"
-        } else {
-            include_str!("query.js")
-        };
-
-        // Corrected construction of tracer_options
-        let tracer_options = GethDebugTracingCallOptions {
-            tracing_options: GethDebugTracingOptions {
-                tracer: Some(GethDebugTracerType::JsTracer(query.to_owned())),
-                ..Default::default()
-            },
-            ..Default::default()
-        };
+        let mut overrides = StateOverride::default();
+        for (address, balance) in &args.balance_overrides {
+            overrides.entry(*address).or_default().balance = Some(*balance);
+        }
+        for (address, code) in &args.code_overrides {
+            overrides.entry(*address).or_default().code = Some(code.clone());
+        }
+        for (address, nonce) in &args.nonce_overrides {
+            overrides.entry(*address).or_default().nonce = Some(*nonce);
+        }
+        for (address, slot, value) in &args.storage_overrides {
+            let account = overrides.entry(*address).or_default();
+            account
+                .state_diff
+                .get_or_insert_with(Default::default)
+                .insert(*slot, *value);
+        }
 
-        // Use the latest block; alternatively, this can be made configurable
-        let block_id = BlockId::latest();
+        Self::trace_call(
+            provider,
+            tx_request,
+            args.block,
+            overrides,
+            args.tracer.use_native_tracer,
+        )
+        .await
+    }
 
-        let GethTrace::JS(json) = provider
-            .debug_trace_call(tx_request.clone(), block_id, tracer_options)
-            .await?
-        else {
-            bail!("Malformed tracing result");
-        };
+    /// Traces a call against the given block without sending a transaction.
+    pub async fn trace_call(
+        provider: &impl Provider,
+        tx_request: TransactionRequest,
+        block: BlockId,
+        overrides: StateOverride,
+        use_native_tracer: bool,
+    ) -> Result<Self> {
+        let overrides = (!overrides.is_empty()).then_some(overrides);
+        let json = trace_with(use_native_tracer, |tracer| {
+            let mut config = tracer.config();
+            if let Some(overrides) = &overrides {
+                config["stateOverrides"] = json!(overrides);
+            }
+            let params = (tx_request.clone(), block, config);
+            async move { provider.raw_request("debug_traceCall".into(), params).await }
+        })
+        .await?;
 
-        if let Value::Array(arr) = json.clone() {
+        let normalized = normalize(json.clone())?;
+        if let Value::Array(arr) = &normalized {
             if arr.is_empty() {
                 bail!("No trace frames found.");
             }
         }
         // Parse the trace frames
-        let top_frame = TraceFrame::parse_frame(None, json.clone())?;
+        let top_frame = TraceFrame::parse_frame(None, normalized)?;
 
         Ok(Self {
             top_frame,
@@ -144,6 +188,126 @@ impl Trace {
     }
 }
 
+/// The tracer used to record the hostios of a transaction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tracer {
+    /// The node's built-in `stylusTracer`.
+    Native,
+    /// The JavaScript tracer in `query.js`, which any node can run.
+    Js,
+}
+
+impl Tracer {
+    fn config(self) -> Value {
+        match self {
+            Self::Native => json!({ "tracer": "stylusTracer" }),
+            Self::Js => json!({ "tracer": include_str!("query.js") }),
+        }
+    }
+}
+
+/// Whether the node supports the native tracer, as learned from the first trace.
+static NATIVE_SUPPORTED: Mutex<Option<bool>> = Mutex::new(None);
+
+/// Traces with the native tracer, falling back to the JavaScript one if the node doesn't know it
+/// unless `native_only` is set. The raw output is returned, to be passed through [`normalize`]
+/// before parsing.
+async fn trace_with<F, Fut>(native_only: bool, trace: F) -> Result<Value>
+where
+    F: Fn(Tracer) -> Fut,
+    Fut: Future<Output = TransportResult<Value>>,
+{
+    let supported = *NATIVE_SUPPORTED.lock();
+    if native_only || supported != Some(false) {
+        match trace(Tracer::Native).await {
+            Ok(json) => {
+                *NATIVE_SUPPORTED.lock() = Some(true);
+                return Ok(json);
+            }
+            Err(err)
+                if !native_only
+                    && supported.is_none()
+                    && err
+                        .as_error_resp()
+                        .is_some_and(|resp| tracer_unsupported(&resp.message)) =>
+            {
+                egreyln!("native tracer unavailable, falling back to the JavaScript tracer");
+                *NATIVE_SUPPORTED.lock() = Some(false);
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(trace(Tracer::Js).await?)
+}
+
+/// Whether a node's error message means it has no tracer by the requested name. Nodes without
+/// built-in tracers may instead try to run the name as JavaScript, and fail to resolve it.
+fn tracer_unsupported(message: &str) -> bool {
+    let message = message.to_lowercase();
+    [
+        "tracer not found",
+        "unknown tracer",
+        "unsupported tracer",
+        "tracer not supported",
+        "stylustracer is not defined",
+    ]
+    .iter()
+    .any(|pattern| message.contains(pattern))
+}
+
+/// Normalizes the output of either tracer to the format expected by [`TraceFrame::parse_frame`],
+/// filling in missing data, lowercasing addresses and converting hex quantities to numbers.
+pub fn normalize(json: Value) -> Result<Value> {
+    let steps = match json {
+        Value::Null => vec![],
+        Value::Array(steps) => steps,
+        x => bail!("malformed tracing result: {x}"),
+    };
+    let mut normalized = Vec::with_capacity(steps.len());
+    for step in steps {
+        let Value::Object(mut keys) = step else {
+            bail!("not a valid step: {step}");
+        };
+        for field in ["args", "outs"] {
+            let data = match keys.remove(field) {
+                None | Some(Value::Null) => json!("0x"),
+                Some(Value::String(data)) => json!(data.to_lowercase()),
+                Some(x) => x,
+            };
+            keys.insert(field.into(), data);
+        }
+        for field in ["startInk", "endInk"] {
+            let ink = match keys.get(field) {
+                // frames entered from the EVM have no ink
+                None | Some(Value::Null) => 0,
+                Some(Value::String(ink)) => match ink.strip_prefix("0x") {
+                    Some(hex) => u64::from_str_radix(hex, 16)?,
+                    None => ink.parse()?,
+                },
+                Some(_) => continue,
+            };
+            keys.insert(field.into(), json!(ink));
+        }
+        if let Some(Value::String(address)) = keys.get("address") {
+            let address = address.to_lowercase();
+            keys.insert("address".into(), json!(address));
+        }
+        if let Some(steps) = keys.remove("steps") {
+            keys.insert("steps".into(), normalize(steps)?);
+        }
+        normalized.push(Value::Object(keys));
+    }
+    Ok(Value::Array(normalized))
+}
+
+/// The on-disk format of a saved trace.
+#[derive(Serialize, Deserialize)]
+struct TraceFile {
+    to: Option<Address>,
+    tx: TransactionRequest,
+    json: Value,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ActivationTraceFrame {
     address: Value,
@@ -161,6 +325,61 @@ impl TraceFrame {
         Self { steps, address }
     }
 
+    /// Creates a reader over this frame's steps. When `headless`, divergences are collected
+    /// for a final report rather than panicking.
+    pub fn reader(self, headless: bool) -> FrameReader {
+        FrameReader::new(self, headless)
+    }
+
+    /// The hostios invoked by this frame, in execution order.
+    pub fn steps(&self) -> &[Hostio] {
+        &self.steps
+    }
+
+    /// The frames of the calls made directly by this one, in execution order.
+    pub fn children(&self) -> impl Iterator<Item = &TraceFrame> {
+        self.steps.iter().filter_map(|step| match &step.kind {
+            HostioKind::CallContract { frame, .. }
+            | HostioKind::DelegateCallContract { frame, .. }
+            | HostioKind::StaticCallContract { frame, .. }
+            | HostioKind::EVMCall { frame, .. } => Some(frame),
+            _ => None,
+        })
+    }
+
+    /// Finds the first frame executing the given contract, searching depth-first.
+    pub fn find_address(&self, address: Address) -> Option<&TraceFrame> {
+        if self.address == Some(address) {
+            return Some(self);
+        }
+        self.children()
+            .find_map(|child| child.find_address(address))
+    }
+
+    /// Follows a path of child indices, where `[1, 0]` selects the first call made by the
+    /// second call of this frame.
+    pub fn find_path(&self, path: &[usize]) -> Result<&TraceFrame> {
+        let mut frame = self;
+        for (depth, &index) in path.iter().enumerate() {
+            let Some(child) = frame.children().nth(index) else {
+                bail!(
+                    "frame {} has no call at index {index}",
+                    display_path(&path[..depth])
+                );
+            };
+            frame = child;
+        }
+        Ok(frame)
+    }
+
+    /// The calldata this frame was invoked with, as read via the `read_args` hostio.
+    pub fn calldata(&self) -> Option<&[u8]> {
+        self.steps.iter().find_map(|step| match &step.kind {
+            HostioKind::ReadArgs { args } => Some(&**args),
+            _ => None,
+        })
+    }
+
     pub fn parse_frame(address: Option<Address>, array: Value) -> Result<TraceFrame> {
         let mut frame = TraceFrame::new(address);
 
@@ -168,341 +387,370 @@ impl TraceFrame {
             bail!("not an array: {}", array);
         };
 
-        for step in array {
-            let Value::Object(mut keys) = step else {
-                bail!("not a valid step: {}", step);
-            };
+        for (index, step) in array.into_iter().enumerate() {
+            let hostio =
+                Self::parse_step(step).wrap_err_with(|| format!("failed to parse step {index}"))?;
+            frame.steps.push(hostio);
+        }
+        Ok(frame)
+    }
 
-            macro_rules! get_typed {
-                ($keys:expr, $ty:ident, $name:expr) => {{
-                    let value = match $keys.remove($name) {
-                        Some(name) => name,
-                        None => bail!("object missing {}: {:?}", $name, $keys),
-                    };
-                    match value {
-                        Value::$ty(string) => string,
-                        x => bail!("unexpected type for {}: {}", $name, x),
-                    }
-                }};
-            }
-            macro_rules! get_int {
-                ($name:expr) => {
-                    get_typed!(keys, Number, $name).as_u64().unwrap()
+    fn parse_step(step: Value) -> Result<Hostio> {
+        let Value::Object(mut keys) = step else {
+            bail!("not a valid step: {}", step);
+        };
+
+        macro_rules! get_typed {
+            ($keys:expr, $ty:ident, $name:expr) => {{
+                let value = match $keys.remove($name) {
+                    Some(name) => name,
+                    None => bail!("object missing {}: {:?}", $name, $keys),
                 };
-            }
-            macro_rules! get_hex {
-                ($name:expr) => {{
-                    let data = get_typed!(keys, String, $name);
-                    let data = data
-                        .strip_prefix("0x")
-                        .ok_or_eyre(concat!($name, " does not contain 0x prefix"))?;
-                    hex::decode(data)
-                        .wrap_err(concat!("failed to parse ", $name))?
-                        .into_boxed_slice()
-                }};
-            }
+                match value {
+                    Value::$ty(string) => string,
+                    x => bail!("unexpected type for {}: {}", $name, x),
+                }
+            }};
+        }
+        macro_rules! get_int {
+            ($name:expr) => {
+                get_typed!(keys, Number, $name)
+                    .as_u64()
+                    .ok_or_eyre(concat!($name, " is not a u64"))?
+            };
+        }
+        macro_rules! get_hex {
+            ($name:expr) => {{
+                let data = get_typed!(keys, String, $name);
+                let data = data
+                    .strip_prefix("0x")
+                    .ok_or_eyre(concat!($name, " does not contain 0x prefix"))?;
+                hex::decode(data)
+                    .wrap_err(concat!("failed to parse ", $name))?
+                    .into_boxed_slice()
+            }};
+        }
 
-            let name = get_typed!(keys, String, "name");
-            let mut args = get_hex!("args");
-            let mut outs = get_hex!("outs");
+        let name = get_typed!(keys, String, "name");
+        let mut args = get_hex!("args");
+        let mut outs = get_hex!("outs");
 
-            let start_ink = get_int!("startInk");
-            let end_ink = get_int!("endInk");
+        let start_ink = get_int!("startInk");
+        let end_ink = get_int!("endInk");
 
-            macro_rules! read_data {
-                ($src:ident) => {{
-                    let data = $src;
-                    $src = Box::new([]);
-                    data
-                }};
-            }
-            macro_rules! read_ty {
-                ($src:ident, $ty:ident, $conv:expr) => {{
-                    let size = mem::size_of::<$ty>();
-                    let len = $src.len();
-                    if size > len {
-                        bail!(
-                            "parse {}: want {} bytes; got {}",
-                            stringify!($src),
-                            size,
-                            len
-                        );
-                    }
-                    let (left, right) = $src.split_at(size);
-                    let result = $conv(left);
-                    $src = right.to_vec().into_boxed_slice();
-                    result
-                }};
-            }
-            macro_rules! read_string {
-                ($src:ident) => {{
-                    let conv = |x: &[_]| String::from_utf8_lossy(&x).to_string();
-                    read_ty!($src, String, conv)
-                }};
-            }
-            macro_rules! read_u256 {
-                ($src:ident) => {
-                    read_ty!($src, U256, |x| B256::from_slice(x).into())
-                };
-            }
-            macro_rules! read_b256 {
-                ($src:ident) => {
-                    read_ty!($src, B256, B256::from_slice)
-                };
-            }
-            macro_rules! read_address {
-                ($src:ident) => {
-                    read_ty!($src, Address, Address::from_slice)
-                };
-            }
-            macro_rules! read_num {
-                ($src:ident, $ty:ident) => {{
-                    let conv = |x: &[_]| $ty::from_be_bytes(x.try_into().unwrap());
-                    read_ty!($src, $ty, conv)
-                }};
-            }
-            macro_rules! read_u8 {
-                ($src:ident) => {
-                    read_num!($src, u8)
-                };
-            }
-            macro_rules! read_u16 {
-                ($src:ident) => {
-                    read_num!($src, u16)
-                };
-            }
-            macro_rules! read_u32 {
-                ($src:ident) => {
-                    read_num!($src, u32)
-                };
-            }
-            macro_rules! read_u64 {
-                ($src:ident) => {
-                    read_num!($src, u64)
-                };
-            }
+        macro_rules! read_data {
+            ($src:ident) => {{
+                let data = $src;
+                $src = Box::new([]);
+                data
+            }};
+        }
+        macro_rules! read_ty {
+            ($src:ident, $ty:ident, $conv:expr) => {{
+                let size = mem::size_of::<$ty>();
+                let len = $src.len();
+                if size > len {
+                    bail!(
+                        "parse {}: want {} bytes; got {}",
+                        stringify!($src),
+                        size,
+                        len
+                    );
+                }
+                let (left, right) = $src.split_at(size);
+                let result = $conv(left);
+                $src = right.to_vec().into_boxed_slice();
+                result
+            }};
+        }
+        macro_rules! read_string {
+            ($src:ident) => {
+                String::from_utf8_lossy(&read_data!($src)).to_string()
+            };
+        }
+        macro_rules! read_u256 {
+            ($src:ident) => {
+                read_ty!($src, U256, |x| B256::from_slice(x).into())
+            };
+        }
+        macro_rules! read_b256 {
+            ($src:ident) => {
+                read_ty!($src, B256, B256::from_slice)
+            };
+        }
+        macro_rules! read_address {
+            ($src:ident) => {
+                read_ty!($src, Address, Address::from_slice)
+            };
+        }
+        macro_rules! read_num {
+            ($src:ident, $ty:ident) => {{
+                let conv = |x: &[_]| $ty::from_be_bytes(x.try_into().unwrap());
+                read_ty!($src, $ty, conv)
+            }};
+        }
+        macro_rules! read_u8 {
+            ($src:ident) => {
+                read_num!($src, u8)
+            };
+        }
+        macro_rules! read_u16 {
+            ($src:ident) => {
+                read_num!($src, u16)
+            };
+        }
+        macro_rules! read_u32 {
+            ($src:ident) => {
+                read_num!($src, u32)
+            };
+        }
+        macro_rules! read_u64 {
+            ($src:ident) => {
+                read_num!($src, u64)
+            };
+        }
 
-            macro_rules! frame {
-                () => {{
-                    let address = get_hex!("address");
-                    let address = Address::from_slice(&address);
-                    let steps = keys.remove("steps").unwrap();
-                    TraceFrame::parse_frame(Some(address), steps)?
-                }};
-            }
+        macro_rules! frame {
+            () => {{
+                let address = get_hex!("address");
+                let address = Address::from_slice(&address);
+                let steps = keys.remove("steps").ok_or_eyre("call missing steps")?;
+                TraceFrame::parse_frame(Some(address), steps)?
+            }};
+        }
 
-            use HostioKind::*;
-            let kind = match name.as_str() {
-                "user_entrypoint" => UserEntrypoint {
-                    args_len: read_u32!(args),
-                },
-                "user_returned" => UserReturned {
-                    status: read_u32!(outs),
-                },
-                "read_args" => ReadArgs {
-                    args: read_data!(outs),
-                },
-                "write_result" => WriteResult {
-                    result: read_data!(args),
-                },
-                "exit_early" => ExitEarly {
-                    status: read_u32!(args),
-                },
-                "storage_load_bytes32" => StorageLoadBytes32 {
-                    key: read_b256!(args),
-                    value: read_b256!(outs),
-                },
-                "storage_cache_bytes32" => StorageCacheBytes32 {
-                    key: read_b256!(args),
-                    value: read_b256!(args),
-                },
-                "storage_flush_cache" => StorageFlushCache {
-                    clear: read_u8!(args),
-                },
-                "transient_load_bytes32" => TransientLoadBytes32 {
-                    key: read_b256!(args),
-                    value: read_b256!(outs),
-                },
-                "transient_store_bytes32" => TransientStoreBytes32 {
-                    key: read_b256!(args),
-                    value: read_b256!(args),
-                },
-                "account_balance" => AccountBalance {
-                    address: read_address!(args),
-                    balance: read_u256!(outs),
-                },
-                "account_code" => AccountCode {
-                    address: read_address!(args),
-                    offset: read_u32!(args),
-                    size: read_u32!(args),
-                    code: read_data!(outs),
-                },
-                "account_code_size" => AccountCodeSize {
-                    address: read_address!(args),
-                    size: read_u32!(outs),
-                },
-                "account_codehash" => AccountCodehash {
-                    address: read_address!(args),
-                    codehash: read_b256!(outs),
-                },
-                "block_basefee" => BlockBasefee {
-                    basefee: read_u256!(outs),
-                },
-                "block_coinbase" => BlockCoinbase {
-                    coinbase: read_address!(outs),
-                },
-                "block_gas_limit" => BlockGasLimit {
-                    limit: read_u64!(outs),
-                },
-                "block_number" => BlockNumber {
-                    number: read_u64!(outs),
-                },
-                "block_timestamp" => BlockTimestamp {
-                    timestamp: read_u64!(outs),
-                },
-                "chainid" => Chainid {
-                    chainid: read_u64!(outs),
-                },
-                "contract_address" => ContractAddress {
-                    address: read_address!(outs),
-                },
-                "evm_gas_left" => EvmGasLeft {
-                    gas_left: read_u64!(outs),
-                },
-                "evm_ink_left" => EvmInkLeft {
-                    ink_left: read_u64!(outs),
-                },
-                "math_div" => MathDiv {
-                    a: read_u256!(args),
-                    b: read_u256!(args),
-                    result: read_u256!(outs),
-                },
-                "math_mod" => MathMod {
-                    a: read_u256!(args),
-                    b: read_u256!(args),
-                    result: read_u256!(outs),
-                },
-                "math_pow" => MathPow {
-                    a: read_u256!(args),
-                    b: read_u256!(args),
-                    result: read_u256!(outs),
-                },
-                "math_add_mod" => MathAddMod {
-                    a: read_u256!(args),
-                    b: read_u256!(args),
-                    c: read_u256!(args),
-                    result: read_u256!(outs),
-                },
-                "math_mul_mod" => MathMulMod {
-                    a: read_u256!(args),
-                    b: read_u256!(args),
-                    c: read_u256!(args),
-                    result: read_u256!(outs),
-                },
-                "msg_reentrant" => MsgReentrant {
-                    reentrant: read_u32!(outs) != 0,
-                },
-                "msg_sender" => MsgSender {
-                    sender: read_address!(outs),
-                },
-                "msg_value" => MsgValue {
-                    value: read_b256!(outs),
-                },
-                "native_keccak256" => NativeKeccak256 {
-                    preimage: read_data!(args),
-                    digest: read_b256!(outs),
-                },
-                "tx_gas_price" => TxGasPrice {
-                    gas_price: read_u256!(outs),
-                },
-                "tx_ink_price" => TxInkPrice {
-                    ink_price: read_u32!(outs),
-                },
-                "tx_origin" => TxOrigin {
-                    origin: read_address!(outs),
-                },
-                "pay_for_memory_grow" => PayForMemoryGrow {
-                    pages: read_u16!(args),
-                },
-                "call_contract" => CallContract {
-                    address: read_address!(args),
-                    gas: read_u64!(args),
-                    value: read_u256!(args),
-                    data: read_data!(args),
-                    outs_len: read_u32!(outs),
-                    status: read_u8!(outs),
-                    frame: frame!(),
-                },
-                "delegate_call_contract" => DelegateCallContract {
-                    address: read_address!(args),
-                    gas: read_u64!(args),
-                    data: read_data!(args),
-                    outs_len: read_u32!(outs),
-                    status: read_u8!(outs),
-                    frame: frame!(),
-                },
-                "static_call_contract" => StaticCallContract {
-                    address: read_address!(args),
-                    gas: read_u64!(args),
-                    data: read_data!(args),
-                    outs_len: read_u32!(outs),
-                    status: read_u8!(outs),
-                    frame: frame!(),
-                },
-                "create1" => Create1 {
-                    endowment: read_u256!(args),
-                    code: read_data!(args),
-                    address: read_address!(outs),
-                    revert_data_len: read_u32!(outs),
-                },
-                "create2" => Create2 {
-                    endowment: read_u256!(args),
-                    salt: read_b256!(args),
-                    code: read_data!(args),
-                    address: read_address!(outs),
-                    revert_data_len: read_u32!(outs),
-                },
-                "emit_log" => EmitLog {
-                    topics: read_u32!(args),
-                    data: read_data!(args),
-                },
-                "read_return_data" => ReadReturnData {
-                    offset: read_u32!(args),
-                    size: read_u32!(args),
-                    data: read_data!(outs),
-                },
-                "return_data_size" => ReturnDataSize {
-                    size: read_u32!(outs),
-                },
-                "console_log_text" => ConsoleLogText {
-                    text: read_data!(args),
-                },
-                "console_log" => ConsoleLog {
-                    text: read_string!(args),
-                },
-                x => {
-                    if x.starts_with("evm_") {
-                        EVMCall {
-                            name: x.to_owned(),
-                            frame: frame!(),
-                        }
-                    } else {
-                        todo!("Missing hostio details {x}")
+        use HostioKind::*;
+        let kind = match name.as_str() {
+            "user_entrypoint" => UserEntrypoint {
+                args_len: read_u32!(args),
+            },
+            "user_returned" => UserReturned {
+                status: read_u32!(outs),
+            },
+            "read_args" => ReadArgs {
+                args: read_data!(outs),
+            },
+            "write_result" => WriteResult {
+                result: read_data!(args),
+            },
+            "exit_early" => ExitEarly {
+                status: read_u32!(args),
+            },
+            "storage_load_bytes32" => StorageLoadBytes32 {
+                key: read_b256!(args),
+                value: read_b256!(outs),
+            },
+            "storage_cache_bytes32" => StorageCacheBytes32 {
+                key: read_b256!(args),
+                value: read_b256!(args),
+            },
+            "storage_flush_cache" => StorageFlushCache {
+                clear: read_u8!(args),
+            },
+            "transient_load_bytes32" => TransientLoadBytes32 {
+                key: read_b256!(args),
+                value: read_b256!(outs),
+            },
+            "transient_store_bytes32" => TransientStoreBytes32 {
+                key: read_b256!(args),
+                value: read_b256!(args),
+            },
+            "account_balance" => AccountBalance {
+                address: read_address!(args),
+                balance: read_u256!(outs),
+            },
+            "account_code" => AccountCode {
+                address: read_address!(args),
+                offset: read_u32!(args),
+                size: read_u32!(args),
+                code: read_data!(outs),
+            },
+            "account_code_size" => AccountCodeSize {
+                address: read_address!(args),
+                size: read_u32!(outs),
+            },
+            "account_codehash" => AccountCodehash {
+                address: read_address!(args),
+                codehash: read_b256!(outs),
+            },
+            "block_basefee" => BlockBasefee {
+                basefee: read_u256!(outs),
+            },
+            "block_coinbase" => BlockCoinbase {
+                coinbase: read_address!(outs),
+            },
+            "block_gas_limit" => BlockGasLimit {
+                limit: read_u64!(outs),
+            },
+            "block_number" => BlockNumber {
+                number: read_u64!(outs),
+            },
+            "block_timestamp" => BlockTimestamp {
+                timestamp: read_u64!(outs),
+            },
+            "chainid" => Chainid {
+                chainid: read_u64!(outs),
+            },
+            "contract_address" => ContractAddress {
+                address: read_address!(outs),
+            },
+            "evm_gas_left" => EvmGasLeft {
+                gas_left: read_u64!(outs),
+            },
+            "evm_ink_left" => EvmInkLeft {
+                ink_left: read_u64!(outs),
+            },
+            "math_div" => MathDiv {
+                a: read_u256!(args),
+                b: read_u256!(args),
+                result: read_u256!(outs),
+            },
+            "math_mod" => MathMod {
+                a: read_u256!(args),
+                b: read_u256!(args),
+                result: read_u256!(outs),
+            },
+            "math_pow" => MathPow {
+                a: read_u256!(args),
+                b: read_u256!(args),
+                result: read_u256!(outs),
+            },
+            "math_add_mod" => MathAddMod {
+                a: read_u256!(args),
+                b: read_u256!(args),
+                c: read_u256!(args),
+                result: read_u256!(outs),
+            },
+            "math_mul_mod" => MathMulMod {
+                a: read_u256!(args),
+                b: read_u256!(args),
+                c: read_u256!(args),
+                result: read_u256!(outs),
+            },
+            "msg_reentrant" => MsgReentrant {
+                reentrant: read_u32!(outs) != 0,
+            },
+            "msg_sender" => MsgSender {
+                sender: read_address!(outs),
+            },
+            "msg_value" => MsgValue {
+                value: read_b256!(outs),
+            },
+            "native_keccak256" => NativeKeccak256 {
+                preimage: read_data!(args),
+                digest: read_b256!(outs),
+            },
+            "tx_gas_price" => TxGasPrice {
+                gas_price: read_u256!(outs),
+            },
+            "tx_ink_price" => TxInkPrice {
+                ink_price: read_u32!(outs),
+            },
+            "tx_origin" => TxOrigin {
+                origin: read_address!(outs),
+            },
+            "pay_for_memory_grow" => PayForMemoryGrow {
+                pages: read_u16!(args),
+            },
+            "call_contract" => CallContract {
+                address: read_address!(args),
+                gas: read_u64!(args),
+                value: read_u256!(args),
+                data: read_data!(args),
+                outs_len: read_u32!(outs),
+                status: read_u8!(outs),
+                frame: frame!(),
+            },
+            "delegate_call_contract" => DelegateCallContract {
+                address: read_address!(args),
+                gas: read_u64!(args),
+                data: read_data!(args),
+                outs_len: read_u32!(outs),
+                status: read_u8!(outs),
+                frame: frame!(),
+            },
+            "static_call_contract" => StaticCallContract {
+                address: read_address!(args),
+                gas: read_u64!(args),
+                data: read_data!(args),
+                outs_len: read_u32!(outs),
+                status: read_u8!(outs),
+                frame: frame!(),
+            },
+            "create1" => Create1 {
+                endowment: read_u256!(args),
+                code: read_data!(args),
+                address: read_address!(outs),
+                revert_data_len: read_u32!(outs),
+            },
+            "create2" => Create2 {
+                endowment: read_u256!(args),
+                salt: read_b256!(args),
+                code: read_data!(args),
+                address: read_address!(outs),
+                revert_data_len: read_u32!(outs),
+            },
+            "emit_log" => EmitLog {
+                topics: read_u32!(args),
+                data: read_data!(args),
+            },
+            "read_return_data" => ReadReturnData {
+                offset: read_u32!(args),
+                size: read_u32!(args),
+                data: read_data!(outs),
+            },
+            "return_data_size" => ReturnDataSize {
+                size: read_u32!(outs),
+            },
+            "console_log_text" => ConsoleLogText {
+                text: read_data!(args),
+            },
+            "console_log" => ConsoleLog {
+                text: read_string!(args),
+            },
+            x => {
+                if x.starts_with("evm_") {
+                    EVMCall {
+                        name: x.to_owned(),
+                        frame: frame!(),
+                    }
+                } else {
+                    Unknown {
+                        name: x.to_owned(),
+                        args: read_data!(args),
+                        outs: read_data!(outs),
                     }
                 }
-            };
-
-            assert!(args.is_empty(), "{name}");
-            assert!(outs.is_empty(), "{name}");
+            }
+        };
 
-            frame.steps.push(Hostio {
-                kind,
-                start_ink,
-                end_ink,
-            });
+        if !args.is_empty() || !outs.is_empty() {
+            bail!(
+                "{name} has {} unexpected argument and {} unexpected output bytes",
+                args.len(),
+                outs.len()
+            );
         }
-        Ok(frame)
+        if let EmitLog { topics, data } = &kind {
+            if data.len() < *topics as usize * 32 {
+                bail!("{name} has {topics} topics but only {} bytes", data.len());
+            }
+        }
+
+        Ok(Hostio {
+            kind,
+            start_ink,
+            end_ink,
+        })
+    }
+}
+
+fn display_path(path: &[usize]) -> String {
+    if path.is_empty() {
+        return "<top>".to_string();
     }
+    let path: Vec<_> = path.iter().map(ToString::to_string).collect();
+    path.join(".")
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -706,59 +954,196 @@ pub enum HostioKind {
         name: String,
         frame: TraceFrame,
     },
+    /// A hostio this version of cargo-stylus doesn't know about, such as one added in a newer ArbOS.
+    Unknown {
+        name: String,
+        args: Box<[u8]>,
+        outs: Box<[u8]>,
+    },
 }
 
 #[derive(Debug)]
 pub struct FrameReader {
     frame: TraceFrame,
     steps: VecDeque<Hostio>,
+    /// The number of onchain steps consumed so far.
+    step: usize,
+    /// Whether to record divergences for a report instead of panicking.
+    headless: bool,
+    divergences: Vec<Divergence>,
+}
+
+/// A point where the local build stopped matching the hostios recorded onchain.
+#[derive(Debug, Serialize)]
+pub struct Divergence {
+    /// Index of the onchain step within the replayed frame, if any remained.
+    pub step: Option<usize>,
+    /// The hostio the local build called, if any.
+    pub local: Option<&'static str>,
+    /// The hostio recorded onchain at this step, if any.
+    pub onchain: Option<String>,
+    /// The argument that differed, when the hostios themselves matched.
+    pub argument: Option<ArgumentMismatch>,
+    pub backtrace: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArgumentMismatch {
+    pub name: &'static str,
+    pub local: String,
+    pub onchain: String,
 }
 
 impl FrameReader {
+    fn new(frame: TraceFrame, headless: bool) -> Self {
+        Self {
+            steps: frame.steps.clone().into(),
+            frame,
+            step: 0,
+            headless,
+            divergences: vec![],
+        }
+    }
+
     fn next(&mut self) -> Result<Hostio> {
         match self.steps.pop_front() {
-            Some(item) => Ok(item),
+            Some(item) => {
+                self.step += 1;
+                Ok(item)
+            }
             None => bail!("No next hostio"),
         }
     }
 
+    fn current_step(&self) -> Option<usize> {
+        self.step.checked_sub(1)
+    }
+
+    fn record(
+        &mut self,
+        local: Option<&'static str>,
+        onchain: Option<&HostioKind>,
+        argument: Option<ArgumentMismatch>,
+    ) {
+        self.divergences.push(Divergence {
+            step: self.current_step(),
+            local,
+            onchain: onchain.map(|kind| format!("{kind:?}")),
+            argument,
+            backtrace: Backtrace::force_capture().to_string(),
+        });
+    }
+
+    fn detected(&self, expected: &'static str) {
+        let expected = expected.red();
+        let which = match self.frame.address {
+            Some(call) => format!("call to {}", call.red()),
+            None => "contract deployment".to_string(),
+        };
+        println!("{}", "\n════════ Divergence ════════".red());
+        println!("Divegence detected while simulating a {which} via local assembly.");
+        println!("The simulated environment expected a call to the {expected} Host I/O.",);
+    }
+
+    /// Gets the onchain hostio matching the one the local build called, panicking or exiting
+    /// with the headless report if it doesn't match.
     pub fn next_hostio(&mut self, expected: &'static str) -> Hostio {
-        fn detected(reader: &FrameReader, expected: &'static str) {
-            let expected = expected.red();
-            let which = match reader.frame.address {
-                Some(call) => format!("call to {}", call.red()),
-                None => "contract deployment".to_string(),
-            };
-            println!("{}", "\n════════ Divergence ════════".red());
-            println!("Divegence detected while simulating a {which} via local assembly.");
-            println!("The simulated environment expected a call to the {expected} Host I/O.",);
+        match self.find_hostio(expected) {
+            Some(hostio) => hostio,
+            None if self.headless => self.exit_with_report(None),
+            None => panic!(),
         }
+    }
 
+    /// Finds the onchain hostio matching the one the local build called. If it doesn't match,
+    /// describes the divergence, or records it when headless, and returns `None`, since the two
+    /// sides can't be resynchronized once the local build takes a different path.
+    pub fn find_hostio(&mut self, expected: &'static str) -> Option<Hostio> {
         loop {
             let Ok(hostio) = self.next() else {
-                detected(self, expected);
+                if self.headless {
+                    self.record(Some(expected), None, None);
+                    return None;
+                }
+                self.detected(expected);
                 println!("However, no such call is made onchain. Are you sure this the right contract?\n");
-                panic!();
+                return None;
             };
 
             if hostio.kind.name() == expected {
-                return hostio;
+                return Some(hostio);
             }
 
             let kind = hostio.kind;
             let name = kind.name();
             match name {
                 "pay_for_memory_grow" | "user_entrypoint" | "user_returned" => continue,
+                _ if self.headless => {
+                    self.record(Some(expected), Some(&kind), None);
+                    return None;
+                }
                 _ => {
-                    detected(self, expected);
+                    self.detected(expected);
                     println!("However, onchain there's a call to {name}. Are you sure this the right contract?\n");
                     println!("expected: {}", expected.red());
                     println!("but have: {}\n", kind.debug_red());
-                    panic!();
+                    return None;
                 }
             }
         }
     }
+
+    /// Handles an argument of the current hostio that differs from the one recorded onchain,
+    /// returning whether the replay may continue, which it does when headless.
+    pub fn argument_mismatch(
+        &mut self,
+        hostio: &'static str,
+        name: &'static str,
+        local: String,
+        onchain: String,
+    ) -> bool {
+        let argument = ArgumentMismatch {
+            name,
+            local,
+            onchain,
+        };
+        if self.headless {
+            self.record(Some(hostio), None, Some(argument));
+            return true;
+        }
+        self.detected(hostio);
+        println!("However, the {} argument differs onchain.\n", name.red());
+        println!("local:   {}", argument.local.red());
+        println!("onchain: {}\n", argument.onchain.red());
+        false
+    }
+
+    /// Records any onchain hostios the local build never called, returning every divergence.
+    pub fn finish(mut self) -> Vec<Divergence> {
+        while let Ok(hostio) = self.next() {
+            match hostio.kind.name() {
+                "pay_for_memory_grow" | "user_entrypoint" | "user_returned" => continue,
+                _ => self.record(None, Some(&hostio.kind), None),
+            }
+        }
+        self.divergences
+    }
+
+    /// Prints the headless report for the given exit status and exits the process.
+    fn exit_with_report(&mut self, status: Option<usize>) -> ! {
+        let divergences = mem::take(&mut self.divergences);
+        print_report(status, &divergences);
+        process::exit(1);
+    }
+}
+
+/// Prints the structured report of a headless replay.
+pub fn print_report(status: Option<usize>, divergences: &[Divergence]) {
+    let report = serde_json::json!({
+        "status": status,
+        "divergences": divergences,
+    });
+    println!("{report:#}");
 }
 
 #[cfg(test)]
@@ -846,6 +1231,254 @@ mod tests {
         );
     }
 
+    #[test]
+    fn select_nested_frame() {
+        let trace = r#"
+        [
+          {
+            "name": "evm_call_contract",
+            "args": "0x",
+            "outs": "0x",
+            "startInk": 0,
+            "endInk": 0,
+            "address": "0x457b1ba688e9854bdbed2f473f7510c476a3da09",
+            "steps": []
+          },
+          {
+            "name": "evm_static_call_contract",
+            "args": "0x",
+            "outs": "0x",
+            "startInk": 0,
+            "endInk": 0,
+            "address": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead",
+            "steps": [
+              {
+                "name": "read_args",
+                "args": "0x",
+                "outs": "0xbeef",
+                "startInk": 0,
+                "endInk": 0
+              }
+            ]
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let top_frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+
+        let target = address!("deaddeaddeaddeaddeaddeaddeaddeaddeaddead");
+        let frame = top_frame.find_address(target).expect("frame not found");
+        assert_eq!(frame.address, Some(target));
+        assert_eq!(frame.calldata(), Some([0xbe, 0xef].as_slice()));
+        assert_eq!(top_frame.find_path(&[1]).expect("bad path"), frame);
+        assert_eq!(top_frame.find_path(&[]).expect("bad path"), &top_frame);
+        assert!(top_frame.find_path(&[1, 0]).is_err());
+        assert!(top_frame.find_address(Address::ZERO).is_none());
+    }
+
+    #[test]
+    fn reject_short_logs() {
+        let trace = r#"
+        [
+          {
+            "name": "emit_log",
+            "args": "0x00000001deadbeef",
+            "outs": "0x",
+            "startInk": 1000,
+            "endInk": 900
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let err = TraceFrame::parse_frame(None, json).unwrap_err();
+        assert!(format!("{err:#}").contains("1 topics but only 4 bytes"));
+    }
+
+    #[test]
+    fn headless_divergences() {
+        let trace = r#"
+        [
+          {
+            "name": "msg_sender",
+            "args": "0x",
+            "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead",
+            "startInk": 1000,
+            "endInk": 900
+          },
+          {
+            "name": "block_number",
+            "args": "0x",
+            "outs": "0x0000000000000010",
+            "startInk": 900,
+            "endInk": 800
+          },
+          {
+            "name": "chainid",
+            "args": "0x",
+            "outs": "0x0000000000066eee",
+            "startInk": 800,
+            "endInk": 700
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+        let mut reader = frame.reader(true);
+
+        reader.next_hostio("msg_sender");
+        let hostio = reader.next_hostio("block_number");
+        assert_eq!(hostio.kind, HostioKind::BlockNumber { number: 16 });
+        reader.argument_mismatch("block_number", "number", "17".into(), "16".into());
+
+        let divergences = reader.finish();
+        assert_eq!(divergences.len(), 2);
+        assert_eq!(divergences[0].step, Some(1));
+        assert_eq!(divergences[0].argument.as_ref().unwrap().name, "number");
+        assert_eq!(divergences[1].step, Some(2));
+        assert_eq!(divergences[1].local, None);
+        assert!(divergences[1]
+            .onchain
+            .as_deref()
+            .unwrap()
+            .starts_with("Chainid"));
+
+        // a hostio called only locally stops the replay with a single divergence
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+        let mut reader = frame.reader(true);
+        assert!(reader.find_hostio("block_number").is_none());
+        let divergences = &reader.divergences;
+        assert_eq!(divergences.len(), 1);
+        assert_eq!(divergences[0].step, Some(0));
+        assert_eq!(divergences[0].local, Some("block_number"));
+        assert!(divergences[0]
+            .onchain
+            .as_deref()
+            .unwrap()
+            .starts_with("MsgSender"));
+    }
+
+    #[test]
+    fn normalize_native_output() {
+        let native = r#"
+        [
+          {
+            "name": "evm_call_contract",
+            "address": "0xDeaDDeaDDeaDDeaDDeaDDeaDDeaDDeaDDeaDDeaD",
+            "steps": [
+              {
+                "name": "msg_sender",
+                "args": null,
+                "outs": "0xDEADDEADDEADDEADDEADDEADDEADDEADDEADDEAD",
+                "startInk": "0x3e8",
+                "endInk": 900
+              }
+            ]
+          }
+        ]"#;
+        let json = normalize(serde_json::from_str(native).unwrap()).expect("failed to normalize");
+        assert_eq!(json[0]["args"], "0x");
+        assert_eq!(
+            json[0]["address"],
+            "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead"
+        );
+        assert_eq!(json[0]["steps"][0]["args"], "0x");
+        assert_eq!(json[0]["steps"][0]["startInk"], 1000);
+
+        let frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+        let child = frame.children().next().unwrap();
+        assert_eq!(child.steps()[0].start_ink, 1000);
+    }
+
+    #[test]
+    fn detect_unsupported_tracer() {
+        assert!(tracer_unsupported("tracer not found"));
+        assert!(tracer_unsupported(
+            "ReferenceError: stylusTracer is not defined at <eval>:1:1(0)"
+        ));
+        assert!(!tracer_unsupported("transaction 0x1234 not found"));
+        assert!(!tracer_unsupported("execution timeout"));
+    }
+
+    #[test]
+    fn parse_unknown_and_malformed() {
+        let trace = r#"
+        [
+          {
+            "name": "msg_sender",
+            "args": "0x",
+            "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead",
+            "startInk": 1000,
+            "endInk": 900
+          },
+          {
+            "name": "future_hostio",
+            "args": "0x01",
+            "outs": "0x02",
+            "startInk": 900,
+            "endInk": 800
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+        assert_eq!(
+            frame.steps[1].kind,
+            HostioKind::Unknown {
+                name: "future_hostio".to_owned(),
+                args: Box::new([1]),
+                outs: Box::new([2]),
+            }
+        );
+
+        let trace = r#"
+        [
+          {
+            "name": "msg_sender",
+            "args": "0x",
+            "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead",
+            "startInk": 1000,
+            "endInk": 900
+          },
+          {
+            "name": "block_number",
+            "args": "0x00",
+            "outs": "0x0000000000000010",
+            "startInk": 900,
+            "endInk": 800
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let err = TraceFrame::parse_frame(None, json).unwrap_err();
+        assert_eq!(err.to_string(), "failed to parse step 1");
+    }
+
+    #[test]
+    fn save_and_load() {
+        let trace = r#"
+        [
+          {
+            "name": "msg_sender",
+            "args": "0x",
+            "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead",
+            "startInk": "0x3e8",
+            "endInk": 900
+          }
+        ]"#;
+        let json: Value = serde_json::from_str(trace).expect("failed to parse json");
+        let to = Some(address!("457b1ba688e9854bdbed2f473f7510c476a3da09"));
+        let trace = Trace {
+            top_frame: TraceFrame::parse_frame(to, normalize(json.clone()).unwrap())
+                .expect("failed to parse frame"),
+            tx: TransactionRequest::default().with_input(vec![0xbe, 0xef]),
+            json,
+        };
+
+        let dir = tempfile::tempdir().expect("failed to create temp dir");
+        let path = dir.path().join("trace.json");
+        trace.save(&path).expect("failed to save trace");
+        let loaded = Trace::from_file(&path).expect("failed to load trace");
+        assert_eq!(loaded.top_frame, trace.top_frame);
+        assert_eq!(loaded.tx, trace.tx);
+        assert_eq!(loaded.json, trace.json);
+    }
+
     #[test]
     fn parse_evm_call() {
         let trace = r#"
@@ -0,0 +1,370 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Compares the hostios of two traces, such as a transaction before and after an optimization.
+
+use crate::{
+    trace::{Hostio, HostioKind, Trace, TraceFrame},
+    util::color::{Color, DebugColor},
+    TraceDiffArgs,
+};
+use alloy::{
+    primitives::{TxHash, B256},
+    providers::ProviderBuilder,
+};
+use eyre::Result;
+use std::{collections::BTreeMap, path::Path};
+
+/// How a step of the first trace relates to the second.
+#[derive(Debug, PartialEq)]
+pub enum StepDiff<'a> {
+    Same(&'a Hostio, &'a Hostio),
+    Changed(&'a Hostio, &'a Hostio),
+    Removed(&'a Hostio),
+    Added(&'a Hostio),
+}
+
+/// A value in the first trace and its counterpart in the second, if any.
+pub type Change<T> = (Option<T>, Option<T>);
+
+/// The differences between two frames, and those of the calls they made.
+#[derive(Debug, Default)]
+pub struct FrameDiff<'a> {
+    /// The path of child indices leading to this frame in the first trace.
+    pub path: Vec<usize>,
+    pub steps: Vec<StepDiff<'a>>,
+    pub ink: (u64, u64),
+    pub storage: BTreeMap<B256, Change<B256>>,
+    pub result: Option<Change<&'a [u8]>>,
+    pub children: Vec<FrameDiff<'a>>,
+}
+
+pub async fn trace_diff(args: &TraceDiffArgs) -> Result<()> {
+    let a = load(&args.a, args).await?;
+    let b = load(&args.b, args).await?;
+    let diff = diff_frames(&a.top_frame, &b.top_frame, vec![]);
+    if diff.is_empty() {
+        println!("traces are identical");
+        return Ok(());
+    }
+    diff.print();
+    Ok(())
+}
+
+/// Loads a side of the diff, which is either a saved trace file or a tx hash.
+async fn load(side: &str, args: &TraceDiffArgs) -> Result<Trace> {
+    let path = Path::new(side);
+    match side.parse::<TxHash>() {
+        Ok(tx) if !path.exists() => {
+            let provider = ProviderBuilder::new().connect(&args.endpoint).await?;
+            Trace::new(&provider, tx, args.tracer.use_native_tracer).await
+        }
+        _ => Trace::from_file(path),
+    }
+}
+
+/// The most cells [`align`] will allocate for its LCS table. Beyond this, the differing middle of
+/// two frames is reported as removals and additions without searching for common steps.
+const MAX_LCS_CELLS: usize = 1 << 24;
+
+/// Aligns the steps of two frames along their longest common subsequence, pairing up adjacent
+/// removals and additions of the same hostio as changes.
+pub fn align<'a>(a: &'a [Hostio], b: &'a [Hostio]) -> Vec<StepDiff<'a>> {
+    let same = |(x, y): &(&Hostio, &Hostio)| x.kind == y.kind;
+    let prefix = a.iter().zip(b).take_while(same).count();
+    let (a_rest, b_rest) = (&a[prefix..], &b[prefix..]);
+    let suffix = a_rest
+        .iter()
+        .rev()
+        .zip(b_rest.iter().rev())
+        .take_while(same)
+        .count();
+    let (a_mid, b_mid) = (
+        &a_rest[..a_rest.len() - suffix],
+        &b_rest[..b_rest.len() - suffix],
+    );
+
+    let mut diffs: Vec<_> = a
+        .iter()
+        .zip(b)
+        .take(prefix)
+        .map(|(x, y)| StepDiff::Same(x, y))
+        .collect();
+    align_middle(&mut diffs, a_mid, b_mid);
+    diffs.extend(
+        a[a.len() - suffix..]
+            .iter()
+            .zip(&b[b.len() - suffix..])
+            .map(|(x, y)| StepDiff::Same(x, y)),
+    );
+    diffs
+}
+
+/// Aligns steps that share no common prefix or suffix.
+fn align_middle<'a>(diffs: &mut Vec<StepDiff<'a>>, a: &'a [Hostio], b: &'a [Hostio]) {
+    let (n, m) = (a.len(), b.len());
+    let mut removed: Vec<_> = vec![];
+    let mut added: Vec<_> = vec![];
+    if (n + 1).saturating_mul(m + 1) > MAX_LCS_CELLS {
+        removed.extend(a);
+        added.extend(b);
+        pair_up(diffs, &mut removed, &mut added);
+        return;
+    }
+
+    let width = m + 1;
+    let mut lcs = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * width + j] = match a[i].kind == b[j].kind {
+                true => lcs[(i + 1) * width + j + 1] + 1,
+                false => lcs[(i + 1) * width + j].max(lcs[i * width + j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && a[i].kind == b[j].kind {
+            pair_up(diffs, &mut removed, &mut added);
+            diffs.push(StepDiff::Same(&a[i], &b[j]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i * width + j + 1] >= lcs[(i + 1) * width + j]) {
+            added.push(&b[j]);
+            j += 1;
+        } else {
+            removed.push(&a[i]);
+            i += 1;
+        }
+    }
+    pair_up(diffs, &mut removed, &mut added);
+}
+
+fn pair_up<'a>(
+    diffs: &mut Vec<StepDiff<'a>>,
+    removed: &mut Vec<&'a Hostio>,
+    added: &mut Vec<&'a Hostio>,
+) {
+    let mut next = 0;
+    for old in removed.drain(..) {
+        let name = old.kind.name();
+        match added[next..].iter().position(|new| new.kind.name() == name) {
+            Some(offset) => {
+                let new = added[next + offset];
+                diffs.extend(
+                    added[next..next + offset]
+                        .iter()
+                        .map(|x| StepDiff::Added(x)),
+                );
+                diffs.push(StepDiff::Changed(old, new));
+                next += offset + 1;
+            }
+            None => diffs.push(StepDiff::Removed(old)),
+        }
+    }
+    diffs.extend(added.drain(..).skip(next).map(StepDiff::Added));
+}
+
+fn nested(kind: &HostioKind) -> Option<&TraceFrame> {
+    match kind {
+        HostioKind::CallContract { frame, .. }
+        | HostioKind::DelegateCallContract { frame, .. }
+        | HostioKind::StaticCallContract { frame, .. }
+        | HostioKind::EVMCall { frame, .. } => Some(frame),
+        _ => None,
+    }
+}
+
+fn ink(frame: &TraceFrame) -> u64 {
+    match (frame.steps().first(), frame.steps().last()) {
+        (Some(first), Some(last)) => first.start_ink.saturating_sub(last.end_ink),
+        _ => 0,
+    }
+}
+
+/// The final value written to each storage slot by the frame.
+fn storage_writes(frame: &TraceFrame) -> BTreeMap<B256, B256> {
+    let mut writes = BTreeMap::new();
+    for step in frame.steps() {
+        if let HostioKind::StorageCacheBytes32 { key, value } = step.kind {
+            writes.insert(key, value);
+        }
+    }
+    writes
+}
+
+fn result(frame: &TraceFrame) -> Option<&[u8]> {
+    frame
+        .steps()
+        .iter()
+        .rev()
+        .find_map(|step| match &step.kind {
+            HostioKind::WriteResult { result } => Some(&**result),
+            _ => None,
+        })
+}
+
+pub fn diff_frames<'a>(a: &'a TraceFrame, b: &'a TraceFrame, path: Vec<usize>) -> FrameDiff<'a> {
+    let steps = align(a.steps(), b.steps());
+
+    let mut children = vec![];
+    let mut child = 0;
+    for step in &steps {
+        match step {
+            StepDiff::Same(old, _) | StepDiff::Removed(old) if nested(&old.kind).is_some() => {
+                child += 1;
+            }
+            StepDiff::Changed(old, new) => {
+                if let (Some(x), Some(y)) = (nested(&old.kind), nested(&new.kind)) {
+                    let mut path = path.clone();
+                    path.push(child);
+                    children.push(diff_frames(x, y, path));
+                }
+                if nested(&old.kind).is_some() {
+                    child += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let (old_writes, new_writes) = (storage_writes(a), storage_writes(b));
+    let mut storage = BTreeMap::new();
+    for key in old_writes.keys().chain(new_writes.keys()) {
+        let (old, new) = (old_writes.get(key).copied(), new_writes.get(key).copied());
+        if old != new {
+            storage.insert(*key, (old, new));
+        }
+    }
+
+    let (old_result, new_result) = (result(a), result(b));
+    FrameDiff {
+        path,
+        steps,
+        ink: (ink(a), ink(b)),
+        storage,
+        result: (old_result != new_result).then_some((old_result, new_result)),
+        children,
+    }
+}
+
+impl FrameDiff<'_> {
+    /// Whether the frames executed the same hostios and spent the same ink.
+    pub fn is_empty(&self) -> bool {
+        self.ink.0 == self.ink.1
+            && self
+                .steps
+                .iter()
+                .all(|step| matches!(step, StepDiff::Same(..)))
+    }
+
+    pub fn print(&self) {
+        let path: Vec<_> = self.path.iter().map(|x| x.to_string()).collect();
+        let path = match path.is_empty() {
+            true => "<top>".to_string(),
+            false => path.join("."),
+        };
+        let (old, new) = self.ink;
+        let delta = new as i128 - old as i128;
+        let delta = match delta {
+            ..0 => delta.mint(),
+            0 => delta.grey(),
+            _ => format!("+{delta}").red(),
+        };
+        println!("frame {}: ink {old} → {new} ({delta})", path.lavender());
+
+        for (index, step) in self.steps.iter().enumerate() {
+            match step {
+                StepDiff::Same(..) => {}
+                StepDiff::Removed(old) => println!("  - {index:>4} {}", old.kind.debug_red()),
+                StepDiff::Added(new) => println!("  + {index:>4} {}", new.kind.debug_mint()),
+                StepDiff::Changed(old, new) if nested(&old.kind).is_some() => {
+                    println!("  ~ {index:>4} {} differs", old.kind.name().yellow());
+                }
+                StepDiff::Changed(old, new) => {
+                    println!("  ~ {index:>4} {}", old.kind.debug_red());
+                    println!("    {:>4} {}", "", new.kind.debug_mint());
+                }
+            }
+        }
+        for (key, (old, new)) in &self.storage {
+            println!(
+                "  storage {}: {} → {}",
+                key.debug_lavender(),
+                old.debug_red(),
+                new.debug_mint()
+            );
+        }
+        if let Some((old, new)) = self.result {
+            let hex = |data: Option<&[u8]>| data.map(alloy::hex::encode_prefixed);
+            println!(
+                "  result: {} → {}",
+                hex(old).debug_red(),
+                hex(new).debug_mint()
+            );
+        }
+        for child in &self.children {
+            child.print();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+
+    fn frame(trace: &str) -> TraceFrame {
+        let json: Value = serde_json::from_str(trace).expect("failed to parse json");
+        TraceFrame::parse_frame(None, json).expect("failed to parse frame")
+    }
+
+    #[test]
+    fn align_steps() {
+        let a = frame(
+            r#"[
+              {"name": "msg_sender", "args": "0x", "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead", "startInk": 1000, "endInk": 900},
+              {"name": "block_number", "args": "0x", "outs": "0x0000000000000010", "startInk": 900, "endInk": 800},
+              {"name": "write_result", "args": "0x01", "outs": "0x", "startInk": 800, "endInk": 700}
+            ]"#,
+        );
+        let b = frame(
+            r#"[
+              {"name": "msg_sender", "args": "0x", "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead", "startInk": 1000, "endInk": 950},
+              {"name": "chainid", "args": "0x", "outs": "0x0000000000066eee", "startInk": 950, "endInk": 900},
+              {"name": "write_result", "args": "0x02", "outs": "0x", "startInk": 900, "endInk": 850}
+            ]"#,
+        );
+
+        let diff = diff_frames(&a, &b, vec![]);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.ink, (300, 150));
+        assert!(matches!(diff.steps[0], StepDiff::Same(..)));
+        assert!(matches!(diff.steps[1], StepDiff::Removed(_)));
+        assert!(matches!(diff.steps[2], StepDiff::Added(_)));
+        assert!(matches!(diff.steps[3], StepDiff::Changed(..)));
+        assert_eq!(diff.result, Some((Some(&[1u8][..]), Some(&[2u8][..]))));
+
+        assert!(diff_frames(&a, &a, vec![]).is_empty());
+    }
+
+    #[test]
+    fn diff_ink_only() {
+        let a = frame(
+            r#"[
+              {"name": "msg_sender", "args": "0x", "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead", "startInk": 1000, "endInk": 900}
+            ]"#,
+        );
+        let b = frame(
+            r#"[
+              {"name": "msg_sender", "args": "0x", "outs": "0xdeaddeaddeaddeaddeaddeaddeaddeaddeaddead", "startInk": 1000, "endInk": 950}
+            ]"#,
+        );
+
+        let diff = diff_frames(&a, &b, vec![]);
+        assert!(matches!(diff.steps[..], [StepDiff::Same(..)]));
+        assert_eq!(diff.ink, (100, 50));
+        assert!(!diff.is_empty());
+    }
+}
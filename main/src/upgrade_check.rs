@@ -0,0 +1,276 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Compares the storage layouts of two versions of a contract, flagging changes that would make
+//! the new implementation misread the storage written by the old one.
+
+use crate::{
+    abi_diff::{report, Change, Severity},
+    macros::*,
+    storage::{StorageEntry, StorageLayout},
+    storage_layout::project_layout,
+    util::{
+        color::{Color, DebugColor},
+        sys,
+    },
+    UpgradeCheckArgs,
+};
+use eyre::{OptionExt, Result};
+use std::{collections::BTreeSet, path::Path};
+
+pub fn upgrade_check(args: &UpgradeCheckArgs) -> Result<()> {
+    let old = load_side(&args.old)?;
+    let new = load_side(&args.new)?;
+    report(&check(&old, &new)?, "storage layout")
+}
+
+/// Loads a side of the check, which is a layout file, a project directory, or a git revision of
+/// the project in the current directory.
+fn load_side(side: &str) -> Result<StorageLayout> {
+    let path = Path::new(side);
+    if path.is_dir() {
+        return project_layout(path);
+    }
+    if path.exists() {
+        return StorageLayout::load(path);
+    }
+    greyln!("checking out {}...", side.debug_lavender());
+    sys::with_git_revision(side, project_layout)
+}
+
+/// Classifies the differences between the storage layouts of two versions of a contract.
+pub fn check(old: &StorageLayout, new: &StorageLayout) -> Result<Vec<Change>> {
+    let mut checker = Checker {
+        old,
+        new,
+        changes: vec![],
+    };
+    checker.entries("", &old.storage, &new.storage)?;
+    let mut changes = checker.changes;
+    changes.sort_by_key(|x| std::cmp::Reverse(x.severity));
+    Ok(changes)
+}
+
+struct Checker<'a> {
+    old: &'a StorageLayout,
+    new: &'a StorageLayout,
+    changes: Vec<Change>,
+}
+
+impl Checker<'_> {
+    fn push(&mut self, severity: Severity, description: String) {
+        self.changes.push(Change {
+            severity,
+            description,
+        });
+    }
+
+    /// Compares the variables of a contract, or the members of a struct.
+    fn entries(&mut self, prefix: &str, old: &[StorageEntry], new: &[StorageEntry]) -> Result<()> {
+        let mut matched = BTreeSet::new();
+        for entry in old {
+            let name = format!("{prefix}{}", entry.label);
+            let renamed = || {
+                new.iter().find(|x| {
+                    x.slot == entry.slot
+                        && x.offset == entry.offset
+                        && !old.iter().any(|y| y.label == x.label)
+                })
+            };
+            let updated = match new.iter().find(|x| x.label == entry.label) {
+                Some(updated) => updated,
+                None => match renamed() {
+                    Some(updated) => {
+                        let description = format!("{name} renamed to {}", updated.label);
+                        self.push(Severity::NonBreaking, description);
+                        updated
+                    }
+                    None => {
+                        self.push(Severity::Breaking, format!("{name} removed"));
+                        continue;
+                    }
+                },
+            };
+            matched.insert(updated.label.as_str());
+
+            if entry.slot != updated.slot || entry.offset != updated.offset {
+                let description = format!(
+                    "{name} moved from slot {} offset {} to slot {} offset {}",
+                    entry.slot, entry.offset, updated.slot, updated.offset
+                );
+                self.push(Severity::Breaking, description);
+                continue;
+            }
+            self.types(&name, &entry.ty, &updated.ty, false)?;
+        }
+
+        for entry in new {
+            if !matched.contains(entry.label.as_str()) {
+                let description = format!(
+                    "{prefix}{} added at slot {} offset {}",
+                    entry.label, entry.slot, entry.offset
+                );
+                self.push(Severity::NonBreaking, description);
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares the types of a value stored at the same location, whose size is fixed when it's
+    /// the element of an array.
+    fn types(&mut self, name: &str, old: &str, new: &str, fixed_size: bool) -> Result<()> {
+        let (old, new) = (self.old.ty(old)?, self.new.ty(new)?);
+        let retyped = format!("{name} changed type from {} to {}", old.label, new.label);
+        if old.encoding != new.encoding {
+            self.push(Severity::Breaking, retyped);
+            return Ok(());
+        }
+
+        // structs may be renamed and, unless they're array elements, extended
+        if let (Some(old_members), Some(new_members)) = (&old.members, &new.members) {
+            let (from, to) = (old.size()?, new.size()?);
+            if fixed_size && from != to {
+                let description = format!("{name} changed size from {from} to {to} bytes");
+                self.push(Severity::Breaking, description);
+            }
+            return self.entries(&format!("{name}."), old_members, new_members);
+        }
+
+        let field = |x: &Option<String>| x.clone().ok_or_eyre("malformed storage type");
+        match old.encoding.as_str() {
+            "mapping" => {
+                let old_key = self.old.ty(&field(&old.key)?)?;
+                let new_key = self.new.ty(&field(&new.key)?)?;
+                if old_key.label != new_key.label {
+                    self.push(Severity::Breaking, retyped);
+                    return Ok(());
+                }
+                let (old_value, new_value) = (field(&old.value)?, field(&new.value)?);
+                self.types(&format!("{name}[]"), &old_value, &new_value, false)
+            }
+            "dynamic_array" => {
+                let (old_base, new_base) = (field(&old.base)?, field(&new.base)?);
+                self.types(&format!("{name}[]"), &old_base, &new_base, true)
+            }
+            _ if old.base.is_some() && new.base.is_some() => {
+                let (from, to) = (old.size()?, new.size()?);
+                if from != to {
+                    // growing a static array shifts what follows, which is flagged separately
+                    let severity = match fixed_size || to < from {
+                        true => Severity::Breaking,
+                        false => Severity::NonBreaking,
+                    };
+                    let description = format!("{name} resized from {} to {}", old.label, new.label);
+                    self.push(severity, description);
+                }
+                let (old_base, new_base) = (field(&old.base)?, field(&new.base)?);
+                self.types(&format!("{name}[]"), &old_base, &new_base, true)
+            }
+            _ => {
+                if old.label != new.label {
+                    self.push(Severity::Breaking, retyped);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layout(storage: serde_json::Value, types: serde_json::Value) -> StorageLayout {
+        serde_json::from_value(json!({ "storage": storage, "types": types })).unwrap()
+    }
+
+    #[test]
+    fn classify_upgrades() {
+        let types = json!({
+            "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+            "t_bool": { "encoding": "inplace", "label": "bool", "numberOfBytes": "1" },
+            "t_uint128": { "encoding": "inplace", "label": "uint128", "numberOfBytes": "16" },
+            "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+            "t_mapping(t_address,t_struct(Account)_storage)": {
+                "encoding": "mapping", "label": "mapping(address => struct Account)",
+                "numberOfBytes": "32", "key": "t_address", "value": "t_struct(Account)_storage"
+            },
+            "t_array(t_uint256)dyn_storage": {
+                "encoding": "dynamic_array", "label": "uint256[]", "numberOfBytes": "32",
+                "base": "t_uint256"
+            },
+            "t_array(t_uint128)dyn_storage": {
+                "encoding": "dynamic_array", "label": "uint128[]", "numberOfBytes": "32",
+                "base": "t_uint128"
+            },
+        });
+        let mut old_types = types.clone();
+        old_types["t_struct(Account)_storage"] = json!({
+            "encoding": "inplace", "label": "struct Account", "numberOfBytes": "32",
+            "members": [{ "label": "balance", "slot": "0", "offset": 0, "type": "t_uint256" }]
+        });
+        let mut new_types = types;
+        new_types["t_struct(Account)_storage"] = json!({
+            "encoding": "inplace", "label": "struct Account", "numberOfBytes": "64",
+            "members": [
+                { "label": "balance", "slot": "0", "offset": 0, "type": "t_uint256" },
+                { "label": "frozen", "slot": "1", "offset": 0, "type": "t_bool" },
+            ]
+        });
+
+        let old = layout(
+            json!([
+                { "label": "owner", "slot": "0", "offset": 0, "type": "t_address" },
+                { "label": "paused", "slot": "0", "offset": 20, "type": "t_bool" },
+                { "label": "supply", "slot": "1", "offset": 0, "type": "t_uint256" },
+                { "label": "accounts", "slot": "2", "offset": 0,
+                  "type": "t_mapping(t_address,t_struct(Account)_storage)" },
+                { "label": "history", "slot": "3", "offset": 0,
+                  "type": "t_array(t_uint256)dyn_storage" },
+                { "label": "nonce", "slot": "4", "offset": 0, "type": "t_uint256" },
+            ]),
+            old_types,
+        );
+        let new = layout(
+            json!([
+                { "label": "admin", "slot": "0", "offset": 0, "type": "t_address" },
+                { "label": "supply", "slot": "1", "offset": 0, "type": "t_uint256" },
+                { "label": "accounts", "slot": "2", "offset": 0,
+                  "type": "t_mapping(t_address,t_struct(Account)_storage)" },
+                { "label": "history", "slot": "3", "offset": 0,
+                  "type": "t_array(t_uint128)dyn_storage" },
+                { "label": "version", "slot": "4", "offset": 0, "type": "t_uint256" },
+                { "label": "nonce", "slot": "5", "offset": 0, "type": "t_uint256" },
+            ]),
+            new_types,
+        );
+
+        let changes = check(&old, &new).unwrap();
+        let descriptions = |severity| -> Vec<_> {
+            changes
+                .iter()
+                .filter(|x| x.severity == severity)
+                .map(|x| x.description.as_str())
+                .collect()
+        };
+        assert_eq!(
+            descriptions(Severity::Breaking),
+            [
+                "paused removed",
+                "history[] changed type from uint256 to uint128",
+                "nonce moved from slot 4 offset 0 to slot 5 offset 0",
+            ]
+        );
+        assert_eq!(
+            descriptions(Severity::NonBreaking),
+            [
+                "owner renamed to admin",
+                "accounts[].frozen added at slot 1 offset 0",
+                "version added at slot 4 offset 0",
+            ]
+        );
+
+        assert!(check(&old, &old).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue, JsonAbiExt, Specifier},
+    hex,
+    json_abi::{Function, JsonAbi, Param},
+    sol_types::decode_revert_reason,
+};
+use eyre::{bail, eyre, Result, WrapErr};
+use serde_json::Value;
+
+/// ABI-encodes a call to the function with the given signature, e.g. `transfer(address,uint256)`.
+pub fn encode_call(sig: &str, args: &[String]) -> Result<Vec<u8>> {
+    let function =
+        Function::parse(sig).wrap_err_with(|| format!("invalid function signature: {sig}"))?;
+    let values = coerce_args(&function.inputs, args)
+        .wrap_err_with(|| format!("invalid arguments for {sig}"))?;
+    Ok(function.abi_encode_input(&values)?)
+}
+
+/// Parses command-line arguments as values of the given parameters.
+pub fn coerce_args(params: &[Param], args: &[String]) -> Result<Vec<DynSolValue>> {
+    if args.len() != params.len() {
+        bail!(
+            "mismatch number of arguments (want {}; got {})",
+            params.len(),
+            args.len()
+        );
+    }
+
+    let mut values = Vec::<DynSolValue>::with_capacity(args.len());
+    for (arg, param) in args.iter().zip(params) {
+        let ty = param
+            .resolve()
+            .wrap_err_with(|| format!("could not resolve arg: {param}"))?;
+        let value = ty
+            .coerce_str(arg)
+            .wrap_err_with(|| format!("could not parse arg: {param}"))?;
+        values.push(value);
+    }
+    Ok(values)
+}
+
+/// Formats a decoded value the way it would be passed as an argument.
+pub fn format_value(value: &DynSolValue) -> String {
+    let join = |values: &[DynSolValue]| {
+        let values: Vec<_> = values.iter().map(format_value).collect();
+        values.join(", ")
+    };
+    match value {
+        DynSolValue::Address(address) => address.to_string(),
+        DynSolValue::Bool(value) => value.to_string(),
+        DynSolValue::Int(value, _) => value.to_string(),
+        DynSolValue::Uint(value, _) => value.to_string(),
+        DynSolValue::FixedBytes(word, size) => hex::encode_prefixed(&word[..*size]),
+        DynSolValue::Bytes(bytes) => hex::encode_prefixed(bytes),
+        DynSolValue::String(value) => format!("{value:?}"),
+        DynSolValue::Array(values) | DynSolValue::FixedArray(values) => {
+            format!("[{}]", join(values))
+        }
+        DynSolValue::Tuple(values) => format!("({})", join(values)),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Converts a decoded value to JSON, keeping numbers as decimal strings so that they don't lose
+/// precision.
+pub fn value_to_json(value: &DynSolValue) -> Value {
+    match value {
+        DynSolValue::Bool(value) => Value::Bool(*value),
+        DynSolValue::String(value) => Value::String(value.clone()),
+        DynSolValue::Array(values)
+        | DynSolValue::FixedArray(values)
+        | DynSolValue::Tuple(values) => Value::Array(values.iter().map(value_to_json).collect()),
+        other => Value::String(format_value(other)),
+    }
+}
+
+/// Converts a JSON value to an argument of the given type. Tuples may be objects keyed by the
+/// names of their components, and numbers may be decimal or hex strings to keep their precision.
+pub fn json_to_value(ty: &str, components: &[Param], json: &Value) -> Result<DynSolValue> {
+    if let Some(open) = ty.strip_suffix(']').and_then(|x| x.rfind('[')) {
+        let Value::Array(items) = json else {
+            bail!("expected an array for {ty}, got {json}");
+        };
+        let base = &ty[..open];
+        let values = items
+            .iter()
+            .map(|x| json_to_value(base, components, x))
+            .collect::<Result<Vec<_>>>()?;
+        let len = &ty[open + 1..ty.len() - 1];
+        if len.is_empty() {
+            return Ok(DynSolValue::Array(values));
+        }
+        if len.parse() != Ok(values.len()) {
+            bail!("expected {len} elements for {ty}, got {}", values.len());
+        }
+        return Ok(DynSolValue::FixedArray(values));
+    }
+    if ty == "tuple" {
+        let values = match json {
+            Value::Array(items) if items.len() == components.len() => items.iter().collect(),
+            Value::Object(fields) => components
+                .iter()
+                .map(|x| {
+                    fields
+                        .get(&x.name)
+                        .ok_or_else(|| eyre!("missing tuple field {}", x.name))
+                })
+                .collect::<Result<Vec<_>>>()?,
+            _ => bail!("expected {} tuple fields, got {json}", components.len()),
+        };
+        let values = values
+            .into_iter()
+            .zip(components)
+            .map(|(json, x)| json_to_value(&x.ty, &x.components, json))
+            .collect::<Result<_>>()?;
+        return Ok(DynSolValue::Tuple(values));
+    }
+
+    let ty = DynSolType::parse(ty)?;
+    let value = match json {
+        Value::String(value) if ty == DynSolType::String => DynSolValue::String(value.clone()),
+        Value::String(value) => ty.coerce_str(value)?,
+        Value::Number(_) | Value::Bool(_) => ty.coerce_str(&json.to_string())?,
+        _ => bail!("expected a {ty} value, got {json}"),
+    };
+    Ok(value)
+}
+
+/// Describes revert data, decoding it as one of the ABI's custom errors if possible, and as a
+/// standard `Error(string)` or `Panic(uint256)` otherwise.
+pub fn decode_revert(abi: &JsonAbi, data: &[u8]) -> String {
+    if let Some((selector, args)) = data.split_first_chunk::<4>() {
+        for error in abi.errors() {
+            if error.selector() != selector {
+                continue;
+            }
+            if let Ok(values) = error.abi_decode_input(args) {
+                let values: Vec<_> = values.iter().map(format_value).collect();
+                return format!("{}({})", error.name, values.join(", "));
+            }
+        }
+    }
+    decode_revert_reason(data).unwrap_or_else(|| hex::encode_prefixed(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_transfer() {
+        let args = [
+            "0x000000000000000000000000000000000000dEaD".to_owned(),
+            "1000".to_owned(),
+        ];
+        let calldata = encode_call("transfer(address,uint256)", &args).unwrap();
+        assert_eq!(&calldata[..4], &[0xa9, 0x05, 0x9c, 0xbb]);
+        assert_eq!(calldata.len(), 4 + 2 * 32);
+        assert_eq!(calldata[4 + 31], 0xad);
+        assert_eq!(&calldata[4 + 62..], &[0x03, 0xe8]);
+
+        assert!(encode_call("transfer(address,uint256)", &args[..1]).is_err());
+    }
+
+    #[test]
+    fn convert_json() {
+        let accounts: Param = serde_json::from_value(serde_json::json!({
+            "name": "accounts",
+            "type": "tuple[]",
+            "components": [
+                { "name": "owner", "type": "address" },
+                { "name": "limits", "type": "uint64[2]" },
+            ],
+        }))
+        .unwrap();
+        let json = serde_json::json!([
+            { "owner": "0x000000000000000000000000000000000000dEaD", "limits": [1, "0x10"] },
+            ["0x0000000000000000000000000000000000000001", [3, 4]],
+        ]);
+        let value = json_to_value(&accounts.ty, &accounts.components, &json).unwrap();
+        assert_eq!(
+            format_value(&value),
+            "[(0x000000000000000000000000000000000000dEaD, [1, 16]), \
+             (0x0000000000000000000000000000000000000001, [3, 4])]"
+        );
+
+        let name = json_to_value("string", &[], &"a \"quoted\" name".into()).unwrap();
+        assert_eq!(name, DynSolValue::String("a \"quoted\" name".into()));
+
+        let short = serde_json::json!([{ "owner": "0x0000000000000000000000000000000000000001", "limits": [1] }]);
+        assert!(json_to_value(&accounts.ty, &accounts.components, &short).is_err());
+        assert!(json_to_value("uint8", &[], &256.into()).is_err());
+    }
+
+    #[test]
+    fn decode_reverts() {
+        let abi = JsonAbi::parse(["error BidTooSmall(uint192 bid, uint192 min)"]).unwrap();
+        let error = &abi.errors["BidTooSmall"][0];
+        let values = [
+            DynSolValue::Uint(alloy::primitives::U256::from(1), 192),
+            DynSolValue::Uint(alloy::primitives::U256::from(5), 192),
+        ];
+        let data = error.abi_encode_input(&values).unwrap();
+        assert_eq!(decode_revert(&abi, &data), "BidTooSmall(1, 5)");
+
+        let data = encode_call("Error(string)", &["too late".to_owned()]).unwrap();
+        assert_eq!(decode_revert(&abi, &data), "revert: too late");
+        assert_eq!(decode_revert(&abi, &[0xff, 0xfe]), "0xfffe");
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright 2024, Offchain Labs, Inc.
 // For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
 
+pub mod abi;
 pub mod color;
 pub mod sys;
 pub mod text;
@@ -1,12 +1,12 @@
 // Copyright 2023-2024, Offchain Labs, Inc.
 // For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
 
-use eyre::{Context, Result};
+use eyre::{bail, Context, Result};
 use std::{
     ffi::OsStr,
     fs::File,
     io::{self, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
@@ -37,3 +37,35 @@ pub fn file_or_stdout(path: Option<PathBuf>) -> Result<Box<dyn Write>> {
         None => Box::new(io::stdout().lock()),
     })
 }
+
+/// Runs git, returning its trimmed output.
+fn git(args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .output()
+        .wrap_err("failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?.trim().to_owned())
+}
+
+/// Checks out a git revision in a temporary worktree, calling `f` with the directory of the
+/// current project within it.
+pub fn with_git_revision<T>(revision: &str, f: impl FnOnce(&Path) -> Result<T>) -> Result<T> {
+    // the project may live in a subdirectory of the repository
+    let prefix = git(&["rev-parse", "--show-prefix"])?;
+    git(&["rev-parse", "--verify", &format!("{revision}^{{commit}}")])
+        .wrap_err_with(|| format!("{revision} is neither a file nor a git revision"))?;
+
+    let worktree = tempfile::tempdir()?;
+    let dir = worktree.path().to_string_lossy().to_string();
+    git(&["worktree", "add", "--detach", &dir, revision])?;
+    let result = f(&worktree.path().join(prefix));
+    git(&["worktree", "remove", "--force", &dir])?;
+    result
+}
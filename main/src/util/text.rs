@@ -9,3 +9,17 @@ pub fn decode0x<T: AsRef<str>>(text: T) -> Result<Vec<u8>> {
     let text = text.strip_prefix("0x").unwrap_or(text);
     Ok(hex::decode(text)?)
 }
+
+/// Converts a name like `stylus-hello_world` to `StylusHelloWorld`.
+pub fn pascal_case(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|x| x.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default()
+        })
+        .collect()
+}
@@ -0,0 +1,816 @@
+// Copyright 2025, Offchain Labs, Inc.
+// For licensing, see https://github.com/OffchainLabs/cargo-stylus/blob/main/licenses/COPYRIGHT.md
+
+//! Replays a transaction by running the contract's WASM in an embedded wasmer runtime.
+//! Unlike the native replay in [`crate::hostio`], this executes the very binary that is
+//! deployed onchain, answering each `vm_hooks` import from the recorded trace.
+
+use crate::trace::{Divergence, FrameReader, HostioKind::*, TraceFrame};
+use crate::util::color::{Color, DebugColor};
+use eyre::{bail, eyre, Result, WrapErr};
+use std::{fs, mem, path::Path};
+use wasmer::{
+    imports, Function, FunctionEnv, FunctionEnvMut, Instance, Memory, MemoryAccessError, Module,
+    Store,
+};
+
+/// The outcome of a replay in the embedded runtime.
+pub struct WasmReplay {
+    /// The status returned by `user_entrypoint`, or passed to `exit_early`, unless the replay
+    /// diverged.
+    pub status: Option<u32>,
+    /// The ink consumed by each hostio, in execution order.
+    pub ink: Vec<(&'static str, u64)>,
+    pub divergences: Vec<Divergence>,
+}
+
+impl WasmReplay {
+    pub fn print_ink(&self) {
+        let mut total = 0;
+        for (step, (name, ink)) in self.ink.iter().enumerate() {
+            println!("{step:>5} {name:<28} {}", ink.debug_lavender());
+            total += ink;
+        }
+        println!("{:>5} {:<28} {}", "", "total", total.debug_mint());
+    }
+}
+
+struct ReplayEnv {
+    reader: Option<FrameReader>,
+    memory: Option<Memory>,
+    ink: Vec<(&'static str, u64)>,
+}
+
+impl ReplayEnv {
+    fn reader(&mut self) -> &mut FrameReader {
+        self.reader.as_mut().expect("reader taken")
+    }
+}
+
+/// Errors that unwind out of the WASM back to the host.
+#[derive(thiserror::Error, Debug)]
+enum Escape {
+    #[error(transparent)]
+    Memory(#[from] MemoryAccessError),
+    #[error("program exited early with status {0}")]
+    Exit(u32),
+    #[error("replay diverged from the onchain trace")]
+    Diverged,
+}
+
+type Env<'a> = FunctionEnvMut<'a, ReplayEnv>;
+type Escapes<T = ()> = Result<T, Escape>;
+
+macro_rules! frame {
+    ($env:ident, $name:literal, $dec:pat) => {
+        let data = $env.data_mut();
+        let Some(hostio) = data.reader().find_hostio($name) else {
+            return Err(Escape::Diverged);
+        };
+        data.ink
+            .push(($name, hostio.start_ink.saturating_sub(hostio.end_ink)));
+        let $dec = hostio.kind else { unreachable!() };
+    };
+}
+
+macro_rules! check {
+    ($env:ident, $hostio:literal, $name:ident, $local:expr, $onchain:expr) => {
+        let (local, onchain) = (&$local, &$onchain);
+        if local != onchain
+            && !$env.data_mut().reader().argument_mismatch(
+                $hostio,
+                stringify!($name),
+                format!("{local:?}"),
+                format!("{onchain:?}"),
+            )
+        {
+            return Err(Escape::Diverged);
+        }
+    };
+}
+
+fn read(env: &Env, ptr: u32, len: u32) -> Escapes<Vec<u8>> {
+    let memory = env.data().memory.clone().expect("memory not set");
+    let mut data = vec![0; len as usize];
+    memory.view(env).read(ptr.into(), &mut data)?;
+    Ok(data)
+}
+
+fn read_fixed<const N: usize>(env: &Env, ptr: u32) -> Escapes<[u8; N]> {
+    let memory = env.data().memory.clone().expect("memory not set");
+    let mut data = [0; N];
+    memory.view(env).read(ptr.into(), &mut data)?;
+    Ok(data)
+}
+
+fn write(env: &Env, ptr: u32, data: &[u8]) -> Escapes {
+    let memory = env.data().memory.clone().expect("memory not set");
+    memory.view(env).write(ptr.into(), data)?;
+    Ok(())
+}
+
+fn read_args(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "read_args", ReadArgs { args });
+    write(&env, dest, &args)
+}
+
+fn write_result(mut env: Env, data: u32, len: u32) -> Escapes {
+    frame!(env, "write_result", WriteResult { result });
+    check!(env, "write_result", result, read(&env, data, len)?, *result);
+    Ok(())
+}
+
+fn exit_early(mut env: Env, status: u32) -> Escapes {
+    frame!(env, "exit_early", ExitEarly { status: onchain });
+    check!(env, "exit_early", status, status, onchain);
+    Err(Escape::Exit(status))
+}
+
+fn storage_load_bytes32(mut env: Env, key: u32, dest: u32) -> Escapes {
+    frame!(
+        env,
+        "storage_load_bytes32",
+        StorageLoadBytes32 { key: k, value }
+    );
+    check!(env, "storage_load_bytes32", key, read_fixed(&env, key)?, k);
+    write(&env, dest, value.as_slice())
+}
+
+fn storage_cache_bytes32(mut env: Env, key: u32, value: u32) -> Escapes {
+    frame!(
+        env,
+        "storage_cache_bytes32",
+        StorageCacheBytes32 { key: k, value: v }
+    );
+    check!(env, "storage_cache_bytes32", key, read_fixed(&env, key)?, k);
+    check!(
+        env,
+        "storage_cache_bytes32",
+        value,
+        read_fixed(&env, value)?,
+        v
+    );
+    Ok(())
+}
+
+fn storage_flush_cache(mut env: Env, clear: u32) -> Escapes {
+    frame!(
+        env,
+        "storage_flush_cache",
+        StorageFlushCache { clear: onchain }
+    );
+    check!(env, "storage_flush_cache", clear, clear, onchain as u32);
+    Ok(())
+}
+
+fn transient_load_bytes32(mut env: Env, key: u32, dest: u32) -> Escapes {
+    frame!(
+        env,
+        "transient_load_bytes32",
+        TransientLoadBytes32 { key: k, value }
+    );
+    check!(
+        env,
+        "transient_load_bytes32",
+        key,
+        read_fixed(&env, key)?,
+        k
+    );
+    write(&env, dest, value.as_slice())
+}
+
+fn transient_store_bytes32(mut env: Env, key: u32, value: u32) -> Escapes {
+    frame!(
+        env,
+        "transient_store_bytes32",
+        TransientStoreBytes32 { key: k, value: v }
+    );
+    check!(
+        env,
+        "transient_store_bytes32",
+        key,
+        read_fixed(&env, key)?,
+        k
+    );
+    check!(
+        env,
+        "transient_store_bytes32",
+        value,
+        read_fixed(&env, value)?,
+        v
+    );
+    Ok(())
+}
+
+fn account_balance(mut env: Env, address: u32, dest: u32) -> Escapes {
+    frame!(
+        env,
+        "account_balance",
+        AccountBalance {
+            address: a,
+            balance
+        }
+    );
+    check!(
+        env,
+        "account_balance",
+        address,
+        read_fixed(&env, address)?,
+        a
+    );
+    write(&env, dest, &balance.to_be_bytes::<32>())
+}
+
+fn account_code(mut env: Env, address: u32, offset: u32, size: u32, dest: u32) -> Escapes<u32> {
+    frame!(
+        env,
+        "account_code",
+        AccountCode {
+            address: a,
+            offset: o,
+            size: s,
+            code
+        }
+    );
+    check!(env, "account_code", address, read_fixed(&env, address)?, a);
+    check!(env, "account_code", offset, offset, o);
+    check!(env, "account_code", size, size, s);
+    write(&env, dest, &code)?;
+    Ok(code.len() as u32)
+}
+
+fn account_code_size(mut env: Env, address: u32) -> Escapes<u32> {
+    frame!(
+        env,
+        "account_code_size",
+        AccountCodeSize { address: a, size }
+    );
+    check!(
+        env,
+        "account_code_size",
+        address,
+        read_fixed(&env, address)?,
+        a
+    );
+    Ok(size)
+}
+
+fn account_codehash(mut env: Env, address: u32, dest: u32) -> Escapes {
+    frame!(
+        env,
+        "account_codehash",
+        AccountCodehash {
+            address: a,
+            codehash
+        }
+    );
+    check!(
+        env,
+        "account_codehash",
+        address,
+        read_fixed(&env, address)?,
+        a
+    );
+    write(&env, dest, codehash.as_slice())
+}
+
+fn block_basefee(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "block_basefee", BlockBasefee { basefee });
+    write(&env, dest, &basefee.to_be_bytes::<32>())
+}
+
+fn block_coinbase(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "block_coinbase", BlockCoinbase { coinbase });
+    write(&env, dest, coinbase.as_slice())
+}
+
+fn block_gas_limit(mut env: Env) -> Escapes<u64> {
+    frame!(env, "block_gas_limit", BlockGasLimit { limit });
+    Ok(limit)
+}
+
+fn block_number(mut env: Env) -> Escapes<u64> {
+    frame!(env, "block_number", BlockNumber { number });
+    Ok(number)
+}
+
+fn block_timestamp(mut env: Env) -> Escapes<u64> {
+    frame!(env, "block_timestamp", BlockTimestamp { timestamp });
+    Ok(timestamp)
+}
+
+fn chainid(mut env: Env) -> Escapes<u64> {
+    frame!(env, "chainid", Chainid { chainid });
+    Ok(chainid)
+}
+
+fn call_contract(
+    mut env: Env,
+    address: u32,
+    calldata: u32,
+    calldata_len: u32,
+    value: u32,
+    gas: u64,
+    return_data_len: u32,
+) -> Escapes<u32> {
+    frame!(
+        env,
+        "call_contract",
+        CallContract {
+            address: a,
+            data,
+            gas: g,
+            value: v,
+            outs_len,
+            status,
+            ..
+        }
+    );
+    check!(env, "call_contract", address, read_fixed(&env, address)?, a);
+    check!(
+        env,
+        "call_contract",
+        data,
+        read(&env, calldata, calldata_len)?,
+        *data
+    );
+    check!(
+        env,
+        "call_contract",
+        value,
+        read_fixed(&env, value)?,
+        v.to_be_bytes::<32>()
+    );
+    check!(env, "call_contract", gas, gas, g);
+    write(&env, return_data_len, &outs_len.to_le_bytes())?;
+    Ok(status.into())
+}
+
+fn delegate_call_contract(
+    mut env: Env,
+    address: u32,
+    calldata: u32,
+    calldata_len: u32,
+    gas: u64,
+    return_data_len: u32,
+) -> Escapes<u32> {
+    frame!(
+        env,
+        "delegate_call_contract",
+        DelegateCallContract {
+            address: a,
+            data,
+            gas: g,
+            outs_len,
+            status,
+            ..
+        }
+    );
+    check!(
+        env,
+        "delegate_call_contract",
+        address,
+        read_fixed(&env, address)?,
+        a
+    );
+    check!(
+        env,
+        "delegate_call_contract",
+        data,
+        read(&env, calldata, calldata_len)?,
+        *data
+    );
+    check!(env, "delegate_call_contract", gas, gas, g);
+    write(&env, return_data_len, &outs_len.to_le_bytes())?;
+    Ok(status.into())
+}
+
+fn static_call_contract(
+    mut env: Env,
+    address: u32,
+    calldata: u32,
+    calldata_len: u32,
+    gas: u64,
+    return_data_len: u32,
+) -> Escapes<u32> {
+    frame!(
+        env,
+        "static_call_contract",
+        StaticCallContract {
+            address: a,
+            data,
+            gas: g,
+            outs_len,
+            status,
+            ..
+        }
+    );
+    check!(
+        env,
+        "static_call_contract",
+        address,
+        read_fixed(&env, address)?,
+        a
+    );
+    check!(
+        env,
+        "static_call_contract",
+        data,
+        read(&env, calldata, calldata_len)?,
+        *data
+    );
+    check!(env, "static_call_contract", gas, gas, g);
+    write(&env, return_data_len, &outs_len.to_le_bytes())?;
+    Ok(status.into())
+}
+
+fn contract_address(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "contract_address", ContractAddress { address });
+    write(&env, dest, address.as_slice())
+}
+
+fn create1(
+    mut env: Env,
+    code: u32,
+    code_len: u32,
+    endowment: u32,
+    contract: u32,
+    revert_data_len: u32,
+) -> Escapes {
+    frame!(
+        env,
+        "create1",
+        Create1 {
+            code: c,
+            endowment: e,
+            address,
+            revert_data_len: len
+        }
+    );
+    check!(env, "create1", code, read(&env, code, code_len)?, *c);
+    check!(
+        env,
+        "create1",
+        endowment,
+        read_fixed(&env, endowment)?,
+        e.to_be_bytes::<32>()
+    );
+    write(&env, contract, address.as_slice())?;
+    write(&env, revert_data_len, &len.to_le_bytes())
+}
+
+fn create2(
+    mut env: Env,
+    code: u32,
+    code_len: u32,
+    endowment: u32,
+    salt: u32,
+    contract: u32,
+    revert_data_len: u32,
+) -> Escapes {
+    frame!(
+        env,
+        "create2",
+        Create2 {
+            code: c,
+            endowment: e,
+            salt: s,
+            address,
+            revert_data_len: len
+        }
+    );
+    check!(env, "create2", code, read(&env, code, code_len)?, *c);
+    check!(
+        env,
+        "create2",
+        endowment,
+        read_fixed(&env, endowment)?,
+        e.to_be_bytes::<32>()
+    );
+    check!(env, "create2", salt, read_fixed(&env, salt)?, s);
+    write(&env, contract, address.as_slice())?;
+    write(&env, revert_data_len, &len.to_le_bytes())
+}
+
+fn emit_log(mut env: Env, data: u32, len: u32, topics: u32) -> Escapes {
+    frame!(env, "emit_log", EmitLog { data: d, topics: t });
+    check!(env, "emit_log", data, read(&env, data, len)?, *d);
+    check!(env, "emit_log", topics, topics, t);
+    Ok(())
+}
+
+fn evm_gas_left(mut env: Env) -> Escapes<u64> {
+    frame!(env, "evm_gas_left", EvmGasLeft { gas_left });
+    Ok(gas_left)
+}
+
+fn evm_ink_left(mut env: Env) -> Escapes<u64> {
+    frame!(env, "evm_ink_left", EvmInkLeft { ink_left });
+    Ok(ink_left)
+}
+
+fn pay_for_memory_grow(mut env: Env, pages: u32) -> Escapes {
+    frame!(env, "pay_for_memory_grow", PayForMemoryGrow { pages: p });
+    check!(env, "pay_for_memory_grow", pages, pages, u32::from(p));
+    Ok(())
+}
+
+macro_rules! math {
+    ($name:ident, $hostio:literal, $kind:ident) => {
+        fn $name(mut env: Env, value: u32, other: u32) -> Escapes {
+            frame!(env, $hostio, $kind { a, b, result });
+            check!(
+                env,
+                $hostio,
+                a,
+                read_fixed(&env, value)?,
+                a.to_be_bytes::<32>()
+            );
+            check!(
+                env,
+                $hostio,
+                b,
+                read_fixed(&env, other)?,
+                b.to_be_bytes::<32>()
+            );
+            write(&env, value, &result.to_be_bytes::<32>())
+        }
+    };
+    ($name:ident, $hostio:literal, $kind:ident, modular) => {
+        fn $name(mut env: Env, value: u32, other: u32, modulus: u32) -> Escapes {
+            frame!(env, $hostio, $kind { a, b, c, result });
+            check!(
+                env,
+                $hostio,
+                a,
+                read_fixed(&env, value)?,
+                a.to_be_bytes::<32>()
+            );
+            check!(
+                env,
+                $hostio,
+                b,
+                read_fixed(&env, other)?,
+                b.to_be_bytes::<32>()
+            );
+            check!(
+                env,
+                $hostio,
+                c,
+                read_fixed(&env, modulus)?,
+                c.to_be_bytes::<32>()
+            );
+            write(&env, value, &result.to_be_bytes::<32>())
+        }
+    };
+}
+
+math!(math_div, "math_div", MathDiv);
+math!(math_mod, "math_mod", MathMod);
+math!(math_pow, "math_pow", MathPow);
+math!(math_add_mod, "math_add_mod", MathAddMod, modular);
+math!(math_mul_mod, "math_mul_mod", MathMulMod, modular);
+
+fn msg_reentrant(mut env: Env) -> Escapes<u32> {
+    frame!(env, "msg_reentrant", MsgReentrant { reentrant });
+    Ok(reentrant.into())
+}
+
+fn msg_sender(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "msg_sender", MsgSender { sender });
+    write(&env, dest, sender.as_slice())
+}
+
+fn msg_value(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "msg_value", MsgValue { value });
+    write(&env, dest, value.as_slice())
+}
+
+fn native_keccak256(mut env: Env, bytes: u32, len: u32, output: u32) -> Escapes {
+    frame!(
+        env,
+        "native_keccak256",
+        NativeKeccak256 { preimage, digest }
+    );
+    check!(
+        env,
+        "native_keccak256",
+        preimage,
+        read(&env, bytes, len)?,
+        *preimage
+    );
+    write(&env, output, digest.as_slice())
+}
+
+fn read_return_data(mut env: Env, dest: u32, offset: u32, size: u32) -> Escapes<u32> {
+    frame!(
+        env,
+        "read_return_data",
+        ReadReturnData {
+            offset: o,
+            size: s,
+            data
+        }
+    );
+    check!(env, "read_return_data", offset, offset, o);
+    check!(env, "read_return_data", size, size, s);
+    write(&env, dest, &data)?;
+    Ok(data.len() as u32)
+}
+
+fn return_data_size(mut env: Env) -> Escapes<u32> {
+    frame!(env, "return_data_size", ReturnDataSize { size });
+    Ok(size)
+}
+
+fn tx_gas_price(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "tx_gas_price", TxGasPrice { gas_price });
+    write(&env, dest, &gas_price.to_be_bytes::<32>())
+}
+
+fn tx_ink_price(mut env: Env) -> Escapes<u32> {
+    frame!(env, "tx_ink_price", TxInkPrice { ink_price });
+    Ok(ink_price)
+}
+
+fn tx_origin(mut env: Env, dest: u32) -> Escapes {
+    frame!(env, "tx_origin", TxOrigin { origin });
+    write(&env, dest, origin.as_slice())
+}
+
+fn log_txt(mut env: Env, text: u32, len: u32) -> Escapes {
+    frame!(env, "console_log_text", ConsoleLogText { text: t });
+    check!(env, "console_log_text", text, read(&env, text, len)?, *t);
+    println!("{}", String::from_utf8_lossy(&t));
+    Ok(())
+}
+
+macro_rules! log {
+    ($name:ident, $ty:ty) => {
+        fn $name(mut env: Env, _value: $ty) -> Escapes {
+            frame!(env, "console_log", ConsoleLog { text });
+            println!("{text}");
+            Ok(())
+        }
+    };
+}
+
+log!(log_i32, i32);
+log!(log_i64, i64);
+log!(log_f32, f32);
+log!(log_f64, f64);
+
+/// Runs the program at `wasm` against the recorded `frame`, calling `user_entrypoint` with
+/// `args_len` bytes of calldata.
+pub fn replay(wasm: &Path, frame: TraceFrame, args_len: u32, headless: bool) -> Result<WasmReplay> {
+    let bytes = fs::read(wasm).wrap_err_with(|| eyre!("failed to read {}", wasm.display()))?;
+    let mut store = Store::default();
+    let module = Module::new(&store, bytes).wrap_err("failed to compile wasm")?;
+
+    let env = FunctionEnv::new(
+        &mut store,
+        ReplayEnv {
+            reader: Some(frame.reader(headless)),
+            memory: None,
+            ink: vec![],
+        },
+    );
+
+    macro_rules! hooks {
+        ($($name:ident),* $(,)?) => {
+            imports! {
+                "vm_hooks" => {
+                    $(stringify!($name) => Function::new_typed_with_env(&mut store, &env, $name),)*
+                },
+                "console" => {
+                    "log_txt" => Function::new_typed_with_env(&mut store, &env, log_txt),
+                    "log_i32" => Function::new_typed_with_env(&mut store, &env, log_i32),
+                    "log_i64" => Function::new_typed_with_env(&mut store, &env, log_i64),
+                    "log_f32" => Function::new_typed_with_env(&mut store, &env, log_f32),
+                    "log_f64" => Function::new_typed_with_env(&mut store, &env, log_f64),
+                },
+            }
+        };
+    }
+    let imports = hooks!(
+        read_args,
+        write_result,
+        exit_early,
+        storage_load_bytes32,
+        storage_cache_bytes32,
+        storage_flush_cache,
+        transient_load_bytes32,
+        transient_store_bytes32,
+        account_balance,
+        account_code,
+        account_code_size,
+        account_codehash,
+        block_basefee,
+        block_coinbase,
+        block_gas_limit,
+        block_number,
+        block_timestamp,
+        chainid,
+        call_contract,
+        delegate_call_contract,
+        static_call_contract,
+        contract_address,
+        create1,
+        create2,
+        emit_log,
+        evm_gas_left,
+        evm_ink_left,
+        pay_for_memory_grow,
+        math_div,
+        math_mod,
+        math_pow,
+        math_add_mod,
+        math_mul_mod,
+        msg_reentrant,
+        msg_sender,
+        msg_value,
+        native_keccak256,
+        read_return_data,
+        return_data_size,
+        tx_gas_price,
+        tx_ink_price,
+        tx_origin,
+    );
+
+    let instance =
+        Instance::new(&mut store, &module, &imports).wrap_err("failed to instantiate wasm")?;
+    let memory = instance.exports.get_memory("memory")?.clone();
+    env.as_mut(&mut store).memory = Some(memory);
+
+    let main = instance
+        .exports
+        .get_typed_function::<u32, u32>(&store, "user_entrypoint")?;
+
+    let status = match main.call(&mut store, args_len) {
+        Ok(status) => Some(status),
+        Err(error) => match error.downcast::<Escape>() {
+            Ok(Escape::Exit(status)) => Some(status),
+            Ok(Escape::Diverged) => None,
+            Ok(Escape::Memory(error)) => bail!("memory access failed: {error}"),
+            Err(error) => bail!("program trapped: {}", error.red()),
+        },
+    };
+
+    let data = env.as_mut(&mut store);
+    let divergences = data.reader.take().expect("reader taken").finish();
+    Ok(WasmReplay {
+        status,
+        ink: mem::take(&mut data.ink),
+        divergences,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn replay_echo() {
+        let trace = r#"
+        [
+          {
+            "name": "read_args",
+            "args": "0x",
+            "outs": "0xbeef",
+            "startInk": 1000,
+            "endInk": 900
+          },
+          {
+            "name": "write_result",
+            "args": "0xbeef",
+            "outs": "0x",
+            "startInk": 800,
+            "endInk": 750
+          }
+        ]"#;
+        let json = serde_json::from_str(trace).expect("failed to parse json");
+        let frame = TraceFrame::parse_frame(None, json).expect("failed to parse frame");
+
+        let wat = r#"
+        (module
+          (import "vm_hooks" "read_args" (func $read_args (param i32)))
+          (import "vm_hooks" "write_result" (func $write_result (param i32 i32)))
+          (memory (export "memory") 1)
+          (func (export "user_entrypoint") (param $len i32) (result i32)
+            (call $read_args (i32.const 0))
+            (call $write_result (i32.const 0) (local.get $len))
+            (i32.const 0)))"#;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(wat.as_bytes()).unwrap();
+
+        let replay = replay(file.path(), frame.clone(), 2, true).expect("failed to replay");
+        assert_eq!(replay.status, Some(0));
+        assert_eq!(replay.ink, vec![("read_args", 100), ("write_result", 50)]);
+        assert!(replay.divergences.is_empty());
+
+        // divergences trap out of the wasm rather than panicking through wasmer
+        let replay = super::replay(file.path(), frame, 1, false).expect("failed to replay");
+        assert_eq!(replay.status, None);
+    }
+}